@@ -0,0 +1,118 @@
+import {ComputeBudgetProgram} from '@solana/web3.js';
+import {AnchorProvider, setProvider, Program, web3, Wallet, workspace} from '@coral-xyz/anchor';
+import * as fs from "node:fs";
+import path from "node:path";
+import dotenv from "dotenv";
+import {SolXenMiner} from '../target/types/sol_xen_miner';
+import yargs from "yargs";
+import {hideBin} from "yargs/helpers";
+
+dotenv.config();
+
+// Simulates mine_hashes_v2 (as the clients send it) of a number of iterations a number of times and reports compute units consumed,
+// to check the --units the clients derive from --iterations, or to compare two builds deployed side by side (--compare)
+async function main() {
+    const network = process.env.ANCHOR_PROVIDER_URL || '';
+    const connection = new web3.Connection(network, 'processed');
+
+    const keyPairFileName = process.env.ANCHOR_WALLET || '';
+    const keyPairString = fs.readFileSync(path.resolve(keyPairFileName), 'utf-8');
+    const keyPair = web3.Keypair.fromSecretKey(new Uint8Array(JSON.parse(keyPairString)));
+    console.log('Using wallet', keyPair.publicKey.toBase58());
+    const wallet = new Wallet(keyPair);
+
+    const yArgs = yargs(hideBin(process.argv))
+        .option('kind', {
+            alias: 'k',
+            type: 'number',
+            default: 0,
+            description: 'Kind of miner 0...3'
+        })
+        .option('runs', {
+            alias: 'r',
+            type: 'number',
+            default: 20,
+            description: 'Number of simulations (one per fresh blockhash)'
+        })
+        .option('iterations', {
+            alias: 'i',
+            type: 'number',
+            default: 72,
+            description: 'Hashes per tx, up to the kind\'s max_hashes'
+        })
+        .option('compare', {
+            alias: 'c',
+            type: 'string',
+            description: 'Program id of another build of the miner (e.g. with `verbose-logs`), initialized the same way, to compare with'
+        })
+        .help()
+        .parseSync()
+
+    const kind = Number(yArgs.kind);
+    const runs = Number(yArgs.runs);
+    const iterations = Number(yArgs.iterations);
+
+    const provider = new AnchorProvider(connection, wallet);
+    setProvider(provider);
+    const program = workspace.SolXenMiner as Program<SolXenMiner>;
+
+    const ethAddress = Array(20).fill(1);
+    // compute units consumed by `runs` simulations of `iterations` hashes, and log bytes per tx
+    const simulate = async (program: Program<SolXenMiner>, iterations: number) => {
+        // shard of the wallet's mine_hashes txs, see `shard_index` in the miner program
+        const [xnShard] = web3.PublicKey.findProgramAddressSync(
+            [Buffer.from("xn-miner-shard"), Buffer.from([kind]), Buffer.from([keyPair.publicKey.toBytes()[0] % 16])],
+            program.programId
+        );
+        const units: number[] = [];
+        let logBytes = 0;
+        for (let i = 0; i < runs; i++) {
+            const tx = await program.methods.mineHashesV2(ethAddress, kind, iterations)
+                .accounts({user: keyPair.publicKey, xnShard})
+                .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({units: 1_400_000})])
+                .transaction();
+            tx.feePayer = keyPair.publicKey;
+            tx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
+            tx.sign(keyPair);
+            const {value} = await connection.simulateTransaction(tx);
+            if (value.err) {
+                console.error('Simulation failed', value.err, value.logs);
+                process.exit(1);
+            }
+            units.push(value.unitsConsumed || 0);
+            logBytes += (value.logs || []).reduce((bytes, log) => bytes + log.length, 0);
+            await new Promise(resolve => setTimeout(resolve, 500));
+        }
+        return {units, logBytes: Math.round(logBytes / runs)};
+    };
+
+    const report = (label: string, units: number[], logBytes: number) => {
+        console.log(label, 'compute units: min', Math.min(...units),
+            'avg', Math.round(units.reduce((a, b) => a + b, 0) / units.length),
+            'max', Math.max(...units));
+        console.log(label, 'log bytes per tx: avg', logBytes);
+    };
+
+    const {units, logBytes} = await simulate(program, iterations);
+    const max = Math.max(...units);
+    report(program.programId.toBase58(), units, logBytes);
+    if (yArgs.compare) {
+        const other = new Program<SolXenMiner>({...program.idl, address: yArgs.compare}, provider);
+        const compared = await simulate(other, iterations);
+        report(yArgs.compare, compared.units, compared.logBytes);
+        console.log('Difference of max compute units:', Math.max(...compared.units) - max);
+    }
+    console.log('Suggested --units (max + 10%):', Math.ceil(max * 1.1),
+        'derived by the clients:', Math.min(100_000 + 15_000 * iterations, 1_400_000));
+
+    // worst cases at 1 and `iterations` hashes give the constants of `sol_xen_sdk::mine_hashes_units`
+    if (iterations > 1) {
+        const maxOne = Math.max(...(await simulate(program, 1)).units);
+        const perIteration = Math.ceil((max - maxOne) / (iterations - 1));
+        console.log('Measured MINE_HASHES_BASE_UNITS:', maxOne - perIteration,
+            'MINE_HASHES_UNITS_PER_ITERATION:', perIteration);
+    }
+}
+
+main().then(() => console.log('Done'))
+    .catch(err => console.error(err));
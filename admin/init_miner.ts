@@ -1,5 +1,5 @@
 import {ComputeBudgetProgram} from '@solana/web3.js';
-import {AnchorProvider, setProvider, Program, web3, Wallet, workspace, utils} from '@coral-xyz/anchor';
+import {AnchorProvider, setProvider, Program, web3, Wallet, workspace, utils, BN} from '@coral-xyz/anchor';
 import * as fs from "node:fs";
 import path from "node:path";
 import dotenv from "dotenv";
@@ -23,6 +23,8 @@ async function main() {
     const [, , , ...params] = process.argv;
     let kind: number;
     let priorityFee: number = 1;
+    let startSlot: number = 268484759;
+    let endSlot: number = 0;
 
     const yArgs = yargs(hideBin(process.argv))
         .option('priorityFee', {
@@ -37,6 +39,23 @@ async function main() {
             demandOption: true,
             description: 'Kind of miner 0...3'
         })
+        .option('startSlot', {
+            alias: 's',
+            type: 'number',
+            default: 268484759,
+            description: 'Slot after which mining is open'
+        })
+        .option('endSlot', {
+            alias: 'e',
+            type: 'number',
+            default: 0,
+            description: 'Slot at which mining is over (0 = until AMP decays to 0)'
+        })
+        .option('mixSlotHash', {
+            type: 'boolean',
+            default: false,
+            description: 'Mix the parent slot hash into mined hashes'
+        })
         .help()
         .parseSync()
     
@@ -50,6 +69,14 @@ async function main() {
         priorityFee = Number(yArgs.priorityFee)
     }
 
+    if (yArgs.startSlot) {
+        startSlot = Number(yArgs.startSlot)
+    }
+
+    if (yArgs.endSlot) {
+        endSlot = Number(yArgs.endSlot)
+    }
+
     if (yArgs.kind !== null || typeof yArgs.kind !== 'undefined') {
         kind = Number(yArgs.kind)
     } else {
@@ -76,8 +103,20 @@ async function main() {
         admin: provider.wallet.publicKey,
     };
 
+    const minerParams = {
+        maxHashes: 72,
+        hashPattern: '420',
+        superhashPattern: '42069',
+        superhashX: 250,
+        ampStart: 300,
+        ampCycleSlots: new BN(100_000),
+        startSlot: new BN(startSlot),
+        endSlot: new BN(endSlot),
+        mixSlotHash: yArgs.mixSlotHash,
+    };
+
     // Send the mint transaction (as Admin)
-    const hash = await program.methods.initMiner(kind).accounts(createAccounts).signers([]).rpc();
+    const hash = await program.methods.initMiner(kind, minerParams).accounts(createAccounts).signers([]).rpc();
     console.log(`Create Mint #${kind} tx hash`, hash)
 
 }
@@ -4,7 +4,7 @@ import * as fs from "node:fs";
 import path from "node:path";
 import * as os from "node:os";
 import dotenv from "dotenv";
-import {getMint, TOKEN_PROGRAM_ID} from "@solana/spl-token";
+import {getMint, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID} from "@solana/spl-token";
 import {SolXenMinter} from '../target/types/sol_xen_minter';
 
 dotenv.config();
@@ -70,17 +70,25 @@ async function main() {
         microLamports: 10000
     });
 
+    // --token2022: Token-2022 mint carrying its metadata in the mint's metadata extension, instead of SPL Token + Metaplex
+    const token2022 = process.argv.includes('--token2022');
 
     // Send the mint transaction (as Admin)
 
-    const hash = await program.methods.createMint(metadata)
-        .accounts(createAccounts)
-        .signers([])
-         .preInstructions([addPriorityFee])
-        .rpc();
+    const hash = token2022
+        ? await program.methods.createMint2022(metadata)
+            .accounts({admin: provider.wallet.publicKey})
+            .signers([])
+            .preInstructions([addPriorityFee])
+            .rpc()
+        : await program.methods.createMint(metadata)
+            .accounts(createAccounts)
+            .signers([])
+            .preInstructions([addPriorityFee])
+            .rpc();
     console.log('Create Mint tx hash', hash)
 
-    const mintAccount = await getMint(provider.connection, mint);
+    const mintAccount = await getMint(provider.connection, mint, undefined, token2022 ? TOKEN_2022_PROGRAM_ID : TOKEN_PROGRAM_ID);
     console.log(mintAccount.address.toBase58())
 
 }
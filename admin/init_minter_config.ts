@@ -0,0 +1,48 @@
+import {AnchorProvider, setProvider, Program, web3, Wallet, workspace} from '@coral-xyz/anchor';
+import * as fs from "node:fs";
+import path from "node:path";
+import dotenv from "dotenv";
+import {SolXenMinter} from "../target/types/sol_xen_minter";
+
+dotenv.config();
+
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+
+async function main() {
+    // Set this to your local cluster or mainnet-beta, testnet, devnet
+    const network = process.env.ANCHOR_PROVIDER_URL || '';
+    const connection = new web3.Connection(network, 'processed');
+
+    // N.B. has to be the minter program's upgrade authority
+    const keyPairFileName = process.env.ANCHOR_WALLET || '';
+    const keyPairString = fs.readFileSync(path.resolve(keyPairFileName), 'utf-8');
+    const keyPair = web3.Keypair.fromSecretKey(new Uint8Array(JSON.parse(keyPairString)));
+    console.log('Using wallet', keyPair.publicKey.toBase58());
+    const wallet = new Wallet(keyPair);
+
+    // Create and set the provider
+    const provider = new AnchorProvider(
+        connection,
+        wallet,
+    );
+    setProvider(provider);
+
+    // Load the program
+    const program = workspace.SolXenMinter as Program<SolXenMinter>;
+
+    const [programData] = web3.PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+
+    const createAccounts = {
+        admin: provider.wallet.publicKey,
+        programData,
+    };
+
+    const hash = await program.methods.initConfig().accounts(createAccounts).signers([]).rpc();
+    console.log('Init Minter config tx hash', hash)
+}
+
+main().then(() => console.log('Done'))
+    .catch(err => console.error(err));
@@ -0,0 +1,93 @@
+import {AnchorProvider, setProvider, Program, web3, Wallet, workspace} from '@coral-xyz/anchor';
+import * as fs from "node:fs";
+import path from "node:path";
+import dotenv from "dotenv";
+import {SolXenMinter} from '../target/types/sol_xen_minter';
+import yargs from "yargs";
+import {hideBin} from "yargs/helpers";
+
+dotenv.config();
+
+async function main() {
+    // Set this to your local cluster or mainnet-beta, testnet, devnet
+    const network = process.env.ANCHOR_PROVIDER_URL || '';
+    const connection = new web3.Connection(network, 'processed');
+
+    // N.B. has to be the admin stored in MinterConfig
+    const keyPairFileName = process.env.ANCHOR_WALLET || '';
+    const keyPairString = fs.readFileSync(path.resolve(keyPairFileName), 'utf-8');
+    const keyPair = web3.Keypair.fromSecretKey(new Uint8Array(JSON.parse(keyPairString)));
+    console.log('Using wallet', keyPair.publicKey.toBase58());
+    const wallet = new Wallet(keyPair);
+
+    const yArgs = yargs(hideBin(process.argv))
+        .command('list', 'List registered miner programs')
+        .command('add', 'Register a miner program for a kind')
+        .command('remove', 'Unregister a miner program for a kind (unless frozen)')
+        .command('freeze', 'Make a registry entry permanent')
+        .option('kind', {
+            alias: 'k',
+            type: 'number',
+            description: 'Kind of miner 0...3'
+        })
+        .option('program', {
+            alias: 'p',
+            type: 'string',
+            description: 'Miner program ID'
+        })
+        .demandCommand(1)
+        .help()
+        .parseSync()
+
+    const [cmd] = yArgs._;
+
+    // Create and set the provider
+    const provider = new AnchorProvider(
+        connection,
+        wallet,
+    );
+    setProvider(provider);
+
+    // Load the program
+    const program = workspace.SolXenMinter as Program<SolXenMinter>;
+
+    const [config] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("xn-minter-config")],
+        program.programId
+    );
+
+    if (cmd === 'list') {
+        const {admin, miners} = await program.account.minterConfig.fetch(config);
+        console.log('Admin', admin.toBase58());
+        for (const {kind, programId, frozen} of miners) {
+            console.log(`kind=${kind} program=${programId.toBase58()}${frozen ? ' (frozen)' : ''}`);
+        }
+        return;
+    }
+
+    if (typeof yArgs.kind === 'undefined' || !yArgs.program) {
+        console.error('Kind and program are required');
+        process.exit(1)
+    }
+    const kind = Number(yArgs.kind);
+    const programId = new web3.PublicKey(yArgs.program);
+    const accounts = {
+        admin: provider.wallet.publicKey,
+    };
+
+    let hash: string;
+    if (cmd === 'add') {
+        hash = await program.methods.addMiner(kind, programId).accounts(accounts).rpc();
+    } else if (cmd === 'remove') {
+        hash = await program.methods.removeMiner(kind, programId).accounts(accounts).rpc();
+    } else if (cmd === 'freeze') {
+        hash = await program.methods.freezeMiner(kind, programId).accounts(accounts).rpc();
+    } else {
+        console.error('Unknown command', cmd);
+        process.exit(1)
+    }
+    console.log(`Minter registry ${cmd} kind=${kind} program=${programId.toBase58()} tx hash`, hash)
+}
+
+main().then(() => console.log('Done'))
+    .catch(err => console.error(err));
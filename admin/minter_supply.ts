@@ -0,0 +1,70 @@
+import {AnchorProvider, setProvider, Program, web3, Wallet, workspace, BN} from '@coral-xyz/anchor';
+import * as fs from "node:fs";
+import path from "node:path";
+import dotenv from "dotenv";
+import {SolXenMinter} from '../target/types/sol_xen_minter';
+import yargs from "yargs";
+import {hideBin} from "yargs/helpers";
+
+dotenv.config();
+
+async function main() {
+    // Set this to your local cluster or mainnet-beta, testnet, devnet
+    const network = process.env.ANCHOR_PROVIDER_URL || '';
+    const connection = new web3.Connection(network, 'processed');
+
+    // N.B. has to be the admin stored in MinterConfig, except for `stats`
+    const keyPairFileName = process.env.ANCHOR_WALLET || '';
+    const keyPairString = fs.readFileSync(path.resolve(keyPairFileName), 'utf-8');
+    const keyPair = web3.Keypair.fromSecretKey(new Uint8Array(JSON.parse(keyPairString)));
+    console.log('Using wallet', keyPair.publicKey.toBase58());
+    const wallet = new Wallet(keyPair);
+
+    const yArgs = yargs(hideBin(process.argv))
+        .command('init', 'Start tracking the minted supply, from the current supply of the mint')
+        .command('cap', 'Set the cap on the minted supply')
+        .command('stats', 'Show the minted supply')
+        .option('cap', {
+            alias: 'c',
+            type: 'string',
+            default: '0',
+            description: 'Cap on the minted supply, in token base units (0 = no cap)'
+        })
+        .demandCommand(1)
+        .help()
+        .parseSync()
+
+    const [cmd] = yArgs._;
+    const cap = new BN(yArgs.cap);
+
+    // Create and set the provider
+    const provider = new AnchorProvider(
+        connection,
+        wallet,
+    );
+    setProvider(provider);
+
+    // Load the program
+    const program = workspace.SolXenMinter as Program<SolXenMinter>;
+    const accounts = {
+        admin: provider.wallet.publicKey,
+    };
+
+    if (cmd === 'init') {
+        const hash = await program.methods.initSupply(cap).accounts(accounts).rpc();
+        console.log('Init supply tx hash', hash)
+    } else if (cmd === 'cap') {
+        const hash = await program.methods.setSupplyCap(cap).accounts(accounts).rpc();
+        console.log('Set supply cap tx hash', hash)
+    } else if (cmd === 'stats') {
+        const stats = await program.methods.getSupplyStats().view();
+        console.log(`Minted=${stats.tokensMinted} cap=${stats.cap} remaining=${stats.remaining ?? 'unlimited'} mint supply=${stats.mintSupply}`);
+        stats.pointsCounters.forEach((points, kind) => console.log(`kind=${kind} points converted=${points}`));
+    } else {
+        console.error('Unknown command', cmd);
+        process.exit(1)
+    }
+}
+
+main().then(() => console.log('Done'))
+    .catch(err => console.error(err));
@@ -4,7 +4,7 @@ import * as fs from "node:fs";
 import path from "node:path";
 import * as os from "node:os";
 import dotenv from "dotenv";
-import {getMint, TOKEN_PROGRAM_ID} from "@solana/spl-token";
+import {getMint} from "@solana/spl-token";
 import {SolXenMinter} from '../target/types/sol_xen_minter';
 
 dotenv.config();
@@ -41,9 +41,12 @@ async function main() {
         program.programId
     );
 
+    // SPL Token or Token-2022, whichever the mint was created with
+    const tokenProgram = (await connection.getAccountInfo(mint))!.owner;
+
     const relinquishControlAccounts = {
         admin: provider.wallet.publicKey,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram,
         mintAccount: mint
     };
 
@@ -55,7 +58,7 @@ async function main() {
         .rpc();
     console.log('Relinquish Mint tx hash', hash)
 
-    const mintAccount = await getMint(provider.connection, mint);
+    const mintAccount = await getMint(provider.connection, mint, undefined, tokenProgram);
     console.log(mintAccount.address.toBase58())
 
 }
@@ -1,42 +1,32 @@
-// use std::io::BufRead;
-use std::mem::size_of;
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::hash::{hash};
+use solana_client::{
+    pubsub_client::PubsubClient,
+    rpc_client::RpcClient,
+    rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
 use solana_sdk::{
-    // sysvar::{rent},
-    system_program,
-    pubkey::Pubkey,
     signature::{Signer, read_keypair_file},
     transaction::Transaction,
-    instruction::{Instruction, AccountMeta},
     compute_budget::ComputeBudgetInstruction,
 };
-use spl_token;
-use spl_associated_token_account::get_associated_token_address_with_program_id;
 use clap::{Parser};
 use std::process;
-use borsh::{BorshSerialize, BorshDeserialize, to_vec, BorshSchema};
 use ethaddr::Address;
 use colored::*;
 use dotenv::dotenv;
 use std::thread;
 use std::time::Duration;
-
-const MINERS: &str = "B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN,2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ,5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ,DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S";
-
-const MINTER: &str = "EPAdVJ5S317jJr2ejgxoA52iptvphGXjPLbqXhZH4n8o";
-
-#[derive(BorshSerialize, Debug)]
-pub struct EthAccount {
-    pub address: [u8; 20],
-    pub address_str: String
-}
-
-#[derive(BorshSerialize, Debug)]
-pub struct MineHashes {
-    pub eth_account: EthAccount,
-    pub _kind: u8,
-}
+use sol_xen_sdk::{
+    eth::{self, EthAddressTotals, EthAggregator},
+    events::{parse_events, HashEventV2},
+    pda,
+    simulate,
+    get_account,
+    GlobalXnRecord,
+    MinerParams,
+    UserEthXnRecord,
+    UserSolXnRecord,
+    UserTokensRecord,
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -49,49 +39,32 @@ struct Args {
     kind: u8,
     #[arg(short, long, default_value_t = 1)]
     fee: u64,
-    #[arg(short, long, default_value_t = 1_180_000)]
-    units: u32,
+    /// Compute unit limit per mine tx, derived from `iterations` if not set
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=sol_xen_sdk::MAX_TX_UNITS as i64))]
+    units: Option<u32>,
+    /// Hashes computed per mine tx, up to the kind's max_hashes and `sol_xen_sdk::MAX_ITERATIONS`
+    #[arg(
+        short,
+        long,
+        default_value_t = sol_xen_sdk::DEFAULT_ITERATIONS,
+        value_parser = clap::value_parser!(u8).range(1..=sol_xen_sdk::MAX_ITERATIONS as i64)
+    )]
+    iterations: u8,
     #[arg(short, long, default_value_t = 1)]
     runs: u32,
     #[arg(short, long, default_value_t = 0.5)]
     delay: f32,
+    /// Number of upcoming slots to evaluate with `predict`
+    #[arg(short, long, default_value_t = 20)]
+    slots: u64,
+    /// With `eth`, keep following the address's hashes as they are mined
+    #[arg(long, default_value_t = false)]
+    watch: bool,
+    /// Websocket endpoint for `--watch`, derived from ANCHOR_PROVIDER_URL if not set
+    #[arg(long)]
+    ws: Option<String>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone)]
-pub struct GlobalXnRecord {
-    pub amp: u16,
-    pub last_amp_slot: u64,
-    pub nonce: [u8; 4],
-    pub kind: u8,
-    pub hashes: u64,
-    pub superhashes: u32,
-    pub points: u128
-} // 38 <> 48
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone)]
-pub struct BoxedGlobalXnRecord {
-    pub data: Box<GlobalXnRecord>
-}
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
-pub struct UserEthXnRecord {
-    pub hashes: u64,  // 8
-    pub superhashes: u32, // 4
-} // 16 == 16
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
-pub struct UserSolXnRecord {
-    pub hashes: u64, // 8
-    pub superhashes: u32, // 4
-    pub points: u128, // 16
-} // 28
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
-pub struct UserTokensRecord {
-    pub points_counters: [u128; 4], // 4 * 16 = 64
-    pub tokens_minted: u128 // 16
-} // 80
-
 fn main() {
     dotenv().ok(); // This line loads the environment variables from the ".env" file.
 
@@ -101,7 +74,8 @@ fn main() {
     let runs = args.runs;
     let kind = args.kind;
     let delay = args.delay;
-    let units = args.units;
+    let iterations = args.iterations;
+    let units = args.units.unwrap_or(sol_xen_sdk::mine_hashes_units(iterations));
     let command = &args.command[..];
 
     // Use ethaddr to parse and validate the Ethereum address with checksum
@@ -115,23 +89,25 @@ fn main() {
 
     println!("Command: {}", command);
     match command {
-        "mine" => do_mine(ethereum_address, _address.0, priority_fee, runs, kind, delay, units),
-        "mint" => do_mint(priority_fee, kind),
+        "mine" => do_mine(ethereum_address, _address.0, priority_fee, runs, kind, delay, units, iterations),
+        "mint" => do_mint(priority_fee),
+        "predict" => do_predict(priority_fee, kind, units, iterations, args.slots),
+        "crank" => do_crank(priority_fee, kind),
+        "import" => do_import(priority_fee),
+        "close" => do_close(priority_fee),
+        "supply" => do_supply(),
+        "eth" => do_eth(&ethereum_address, _address.0, args.watch, args.ws),
         _ => {}
     }
-    ;
 }
 
 // Earn (mine) points by looking for hash patterns in randomized numbers
-fn do_mine(ethereum_address: String, address: [u8; 20], priority_fee: u64, runs: u32, kind: u8, delay: f32, units: u32) {
+#[allow(clippy::too_many_arguments)]
+fn do_mine(ethereum_address: String, address: [u8; 20], priority_fee: u64, runs: u32, kind: u8, delay: f32, units: u32, iterations: u8) {
     let keypair_path = std::env::var("USER_WALLET").expect("USER_WALLET must be set.");
     let url = std::env::var("ANCHOR_PROVIDER_URL").expect("ANCHOR_PROVIDER_URL must be set.");
 
-    let miners_program_ids_str= String::from(MINERS);
-    let miners = miners_program_ids_str.split(',').collect::<Vec<&str>>();
-    assert_eq!(miners.len(), 4, "Bad miners set");
-
-    let program_id = Pubkey::try_from(miners[kind as usize]).expect("Bad program ID");
+    let program_id = sol_xen_sdk::miner_program_id();
 
     println!("Miner Program ID={} kind={}", program_id.to_string().green(), kind.to_string().green());
 
@@ -148,73 +124,28 @@ fn do_mine(ethereum_address: String, address: [u8; 20], priority_fee: u64, runs:
         delay.to_string().green(),
     );
 
-    let (global_xn_record_pda, _global_bump) = Pubkey::find_program_address(
-        &[b"xn-miner-global", kind.to_be_bytes().as_slice()],
-        &program_id
-    );
+    let (global_xn_record_pda, _global_bump) = pda::global_xn_record(kind, &program_id);
     println!("Global XN PDA: {}", global_xn_record_pda.to_string().green());
 
-    let (user_eth_xn_record_pda, _user_eth_bump) = Pubkey::find_program_address(
-        &[
-            b"xn-by-eth",
-            &address.as_slice(),
-            kind.to_be_bytes().as_slice(),
-            program_id.as_ref()
-        ],
-        &program_id
-    );
+    let (user_eth_xn_record_pda, _user_eth_bump) = pda::user_eth_xn_record(&address, kind, &program_id);
     println!("User Eth PDA: {}", user_eth_xn_record_pda.to_string().green());
 
-    let (user_sol_xn_record_pda, _user_sol_bump) = Pubkey::find_program_address(
-        &[
-            b"xn-by-sol",
-            &payer.pubkey().to_bytes(),
-            kind.to_be_bytes().as_slice(),
-            program_id.as_ref()
-        ],
-        &program_id
-    );
+    let (user_sol_xn_record_pda, _user_sol_bump) = pda::user_sol_xn_record(&payer.pubkey(), kind, &program_id);
     println!("User Sol PDA: {}", user_sol_xn_record_pda.to_string().green());
 
-    /*
-    let global_data_raw = client.get_account_data(&global_xn_record_pda).unwrap();
-    let global_data: [u8; size_of::<GlobalXnRecord>() - 10] = global_data_raw.as_slice()[8..46].try_into().unwrap();
-    let global_state = GlobalXnRecord::try_from_slice(global_data.as_ref()).unwrap();
-    println!(
-        "Global State: txs={}, hashes={}, superhashes={}, amp={}",
-        global_state.txs.to_string().green(),
-        global_state.hashes.to_string().green(),
-        global_state.superhashes.to_string().green(),
-        global_state.amp.to_string().green()
-    );
-     */
-
-    let method_name_data = "global:mine_hashes";
-    let digest = hash(method_name_data.as_bytes());
-    let ix_data  = &digest.to_bytes()[0..8];
+    if sol_xen_sdk::fetch_remaining_cycles(&client, kind, &program_id) == Some(0) {
+        println!("{}", "Mining is over; nothing to do".red());
+        return;
+    }
 
     for _run in 0..runs {
-        let address_str = ethereum_address.clone();
-
-        let mint_hashes = MineHashes {
-            eth_account: EthAccount {
-                address,
-                address_str
-            },
-            _kind: kind
-        };
-
-        let instruction = Instruction {
-            program_id,
-            data: [ix_data, to_vec(&mint_hashes).unwrap().as_slice()].concat().to_vec(),
-            accounts: vec![
-                AccountMeta::new(global_xn_record_pda, false),
-                AccountMeta::new(user_eth_xn_record_pda, false),
-                AccountMeta::new(user_sol_xn_record_pda, false),
-                AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new_readonly(system_program::ID, false),
-            ]
-        };
+        let instruction = sol_xen_sdk::mine_hashes_v2(
+            &program_id,
+            &payer.pubkey(),
+            address,
+            kind,
+            iterations
+        );
 
         let compute_budget_instruction_limit = ComputeBudgetInstruction::set_compute_unit_limit(units);
         let compute_budget_instruction_price = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
@@ -233,130 +164,338 @@ fn do_mine(ethereum_address: String, address: [u8; 20], priority_fee: u64, runs:
         let result = client.send_transaction(&transaction);
         match result {
             Ok(signature) => {
-                let maybe_user_account_data_raw = client.get_account_data(&user_eth_xn_record_pda);
-                match maybe_user_account_data_raw {
-                    Ok(user_account_data_raw) => {
-                        let user_data: [u8; size_of::<UserEthXnRecord>() - 4] = user_account_data_raw.as_slice()[8..20].try_into().unwrap();
-                        let user_state = UserEthXnRecord::try_from_slice(user_data.as_ref()).unwrap();
-
-                        let maybe_user_sol_account_data_raw = client.get_account_data(&user_sol_xn_record_pda);
-                        match maybe_user_sol_account_data_raw {
-                            Ok(user_sol_account_data_raw) => {
-                                // 36 32 28
-                                // println!("{} {}", user_sol_account_data_raw.len(), size_of::<UserSolXnRecord>());
-                                let user_sol_data: [u8; size_of::<UserSolXnRecord>() - 4] = user_sol_account_data_raw.as_slice()[8..].try_into().unwrap();
-                                let user_sol_state = UserSolXnRecord::try_from_slice(user_sol_data.as_ref()).unwrap();
-                                println!(
-                                    "Tx={}, hashes={}, superhashes={}, points={}",
-                                    signature.to_string().yellow(),
-                                    user_state.hashes.to_string().yellow(),
-                                    user_state.superhashes.to_string().yellow(),
-                                    (user_sol_state.points / 1_000_000_000).to_string().yellow(),
-                                )
-                            }
-                            Err(_) => println!("Account data not yet ready; skipping")
-                        }
-
-                    }
-                    Err(_) => println!("Account data not yet ready; skipping")
+                let user_state = get_account::<UserEthXnRecord>(&client, &user_eth_xn_record_pda);
+                let user_sol_state = get_account::<UserSolXnRecord>(&client, &user_sol_xn_record_pda);
+                match (user_state, user_sol_state) {
+                    (Some(user_state), Some(user_sol_state)) => println!(
+                        "Tx={}, hashes={}, superhashes={}, points={}",
+                        signature.to_string().yellow(),
+                        user_state.hashes.to_string().yellow(),
+                        user_state.superhashes.to_string().yellow(),
+                        (user_sol_state.points / sol_xen_sdk::DECIMALS).to_string().yellow(),
+                    ),
+                    _ => println!("Account data not yet ready; skipping")
                 }
             },
+            Err(err) if sol_xen_sdk::is_mining_over_error(&err) => {
+                println!("{}", "Mining is over; stopping".red());
+                break;
+            },
             Err(err) => println!("Failed: {:?}", err),
         };
         thread::sleep(Duration::from_secs_f32(delay));
     }
 }
 
-// Mint tokens based on provided evidence of mining points
-fn do_mint(priority_fee: u64, kind: u8) {
-    let keypair_path = std::env::var("USER_WALLET").expect("USER_WALLET must be set.");
+fn print_eth_totals(totals: &EthAddressTotals) {
+    for (kind, kind_totals) in totals.kinds.iter().enumerate() {
+        println!(
+            "Kind={}: hashes={}, superhashes={}",
+            kind,
+            kind_totals.hashes.to_string().yellow(),
+            kind_totals.superhashes.to_string().yellow(),
+        );
+    }
+    println!("Hashes={} Superhashes={}", totals.hashes().to_string().green(), totals.superhashes().to_string().green());
+}
+
+// Hashes attributed to the Ethereum address across all kinds, optionally followed through `HashEventV2` events
+fn do_eth(ethereum_address: &str, address: [u8; 20], watch: bool, ws_url: Option<String>) {
     let url = std::env::var("ANCHOR_PROVIDER_URL").expect("ANCHOR_PROVIDER_URL must be set.");
+    let program_id = sol_xen_sdk::miner_program_id();
+    println!("Miner Program ID={} account={}", program_id.to_string().green(), ethereum_address.green());
 
-    let program_id_minter_str = String::from(MINTER);
-    let program_id_minter = Pubkey::try_from(program_id_minter_str.as_str()).expect("Bad program ID");
+    let client = RpcClient::new(url.clone());
+    println!("Running on: {}", client.url().green());
 
-    let miners_program_ids_str= String::from(MINERS);
-    let miners = miners_program_ids_str.split(',').collect::<Vec<&str>>();
-    assert_eq!(miners.len(), 4, "Bad miners set");
+    let totals = eth::fetch_eth_totals(&client, &program_id, &address);
+    print_eth_totals(&totals);
+    if !watch {
+        return;
+    }
 
-    let program_id_miner = Pubkey::try_from(miners[kind as usize]).expect("Bad program ID");
+    let ws_url = match ws_url.or_else(|| sol_xen_sdk::websocket_url(&url)) {
+        Some(ws_url) => ws_url,
+        None => {
+            eprintln!("Can't derive a websocket URL from {}, set it with --ws", url);
+            process::exit(1);
+        }
+    };
+    let (_subscription, logs) = PubsubClient::logs_subscribe(
+        &ws_url,
+        RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]),
+        RpcTransactionLogsConfig { commitment: None },
+    ).expect("Failed to subscribe to miner logs");
+    let mut aggregator = EthAggregator::with_totals(address, totals);
+    for response in logs {
+        if response.value.err.is_some() {
+            continue;
+        }
+        for event in parse_events::<HashEventV2>(&response.value.logs) {
+            if event.eth_account != address {
+                continue;
+            }
+            println!(
+                "Tx={} slot={} kind={}: +hashes={}, +superhashes={}",
+                response.value.signature.yellow(),
+                event.slot,
+                event.kind,
+                event.hashes,
+                event.superhashes,
+            );
+            print_eth_totals(aggregator.apply(&event));
+        }
+    }
+}
 
-    // println!("Test{:?}", kind.to_be_bytes().as_slice());
-    println!("Program ID={}", program_id_minter.to_string().green());
-    println!("Miner Program ID={}", program_id_miner.to_string().green());
+// Predict hashes, superhashes and points of the next mine tx for upcoming slots
+fn do_predict(priority_fee: u64, kind: u8, units: u32, iterations: u8, slots: u64) {
+    let keypair_path = std::env::var("USER_WALLET").expect("USER_WALLET must be set.");
+    let url = std::env::var("ANCHOR_PROVIDER_URL").expect("ANCHOR_PROVIDER_URL must be set.");
+    let program_id = sol_xen_sdk::miner_program_id();
+    println!("Miner Program ID={} kind={}", program_id.to_string().green(), kind.to_string().green());
 
     let client = RpcClient::new(url);
     println!("Running on: {}", client.url().green());
     let payer = read_keypair_file(&keypair_path).expect("Failed to read keypair file");
+    println!("Using shard={}", sol_xen_sdk::shard_index(&payer.pubkey()).to_string().green());
 
-    println!("Using user wallet={}, fee={}", payer.pubkey().to_string().green(), priority_fee.to_string().green(), );
+    let params = get_account::<MinerParams>(&client, &pda::miner_params(kind, &program_id).0);
+    if params.is_some_and(|params| params.mix_slot_hash) {
+        println!("{}", "Kind mixes slot hashes into mining, outcomes can't be predicted".red());
+        return;
+    }
+    let predictions = match simulate::fetch_predictions(&client, kind, &program_id, &payer.pubkey(), iterations, slots) {
+        Some(predictions) => predictions,
+        None => {
+            eprintln!("Failed to read miner state");
+            process::exit(1);
+        }
+    };
+    if predictions.is_empty() {
+        println!("{}", "Mining is not open in upcoming slots".red());
+        return;
+    }
 
-    let (user_sol_xn_record_pda, _user_bump) = Pubkey::find_program_address(
-        &[
-            b"xn-by-sol",
-            &payer.pubkey().to_bytes(),
-            kind.to_be_bytes().as_slice(),
-            &program_id_miner.to_bytes()
-        ],
-        &program_id_miner
+    for p in &predictions {
+        println!(
+            "Slot={}, amp={}, hashes={}, superhashes={}, points={}",
+            p.slot.to_string().yellow(),
+            p.amp,
+            p.hashes.to_string().yellow(),
+            p.superhashes.to_string().yellow(),
+            (p.points / sol_xen_sdk::DECIMALS).to_string().yellow(),
+        );
+    }
+
+    let total_points: u128 = predictions.iter().map(|p| p.points).sum();
+    let avg_points = total_points / predictions.len() as u128 / sol_xen_sdk::DECIMALS;
+    // base signature fee + priority fee for the whole compute unit limit
+    let fee_lamports = 5_000 + (priority_fee as u128 * units as u128).div_ceil(1_000_000);
+    println!(
+        "Expected points per tx={}, fee per tx={} lamports (~{} points per 1000 lamports)",
+        avg_points.to_string().green(),
+        fee_lamports.to_string().green(),
+        (avg_points * 1_000 / fee_lamports).to_string().green(),
     );
-    println!("User record PDA={} bump={}", user_sol_xn_record_pda.to_string().green(), _user_bump.to_string());
+    println!("N.B. predictions hold until the next mine tx on this shard lands and rotates the nonce");
+}
+
+// Add what was mined on the shards of `kind` to its totals, and store the AMP decay due
+fn do_crank(priority_fee: u64, kind: u8) {
+    let keypair_path = std::env::var("USER_WALLET").expect("USER_WALLET must be set.");
+    let url = std::env::var("ANCHOR_PROVIDER_URL").expect("ANCHOR_PROVIDER_URL must be set.");
+
+    let program_id = sol_xen_sdk::miner_program_id();
+    println!("Miner Program ID={} kind={}", program_id.to_string().green(), kind.to_string().green());
 
-    let (user_token_record_pda, _user_rec_bump) = Pubkey::find_program_address(
+    let client = RpcClient::new(url);
+    println!("Running on: {}", client.url().green());
+    let payer = read_keypair_file(&keypair_path).expect("Failed to read keypair file");
+
+    let transaction = Transaction::new_signed_with_payer(
         &[
-            b"sol-xen-minted",
-            &payer.pubkey().to_bytes(),
+            ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+            sol_xen_sdk::crank_totals(&program_id, kind),
         ],
-        &program_id_minter
+        Some(&payer.pubkey()),
+        &[&payer],
+        client.get_latest_blockhash().unwrap(),
     );
-    println!("User token record PDA={} bump={}", user_token_record_pda.to_string().green(), _user_rec_bump.to_string());
+    match client.send_and_confirm_transaction_with_spinner(&transaction) {
+        Ok(signature) => {
+            let (global_xn_record_pda, _) = pda::global_xn_record(kind, &program_id);
+            let global = get_account::<GlobalXnRecord>(&client, &global_xn_record_pda);
+            println!(
+                "Tx={}, amp={}, hashes={}, superhashes={}, points={}",
+                signature.to_string().yellow(),
+                global.as_ref().map_or("-".to_string(), |g| g.amp.to_string()).yellow(),
+                global.as_ref().map_or("-".to_string(), |g| g.hashes.to_string()).yellow(),
+                global.as_ref().map_or("-".to_string(), |g| g.superhashes.to_string()).yellow(),
+                global.as_ref().map_or("-".to_string(), |g| (g.points / sol_xen_sdk::DECIMALS).to_string()).yellow(),
+            );
+        },
+        Err(err) => println!("Failed: {:?}", err),
+    }
+}
 
-    let (mint_pda, _mint_bump) = Pubkey::find_program_address(
-        &[b"mint"],
-        &program_id_minter
-    );
-    println!("Mint PDA={}", mint_pda.to_string().green());
+// Import points mined with the legacy per-kind miners into the records of the single miner program
+fn do_import(priority_fee: u64) {
+    let keypair_path = std::env::var("USER_WALLET").expect("USER_WALLET must be set.");
+    let url = std::env::var("ANCHOR_PROVIDER_URL").expect("ANCHOR_PROVIDER_URL must be set.");
 
-    let associate_token_program = Pubkey::try_from("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
+    let program_id = sol_xen_sdk::miner_program_id();
+    println!("Miner Program ID={}", program_id.to_string().green());
 
-    let user_token_account = get_associated_token_address_with_program_id(
-        &payer.pubkey(),
-        &mint_pda,
-        &spl_token::ID
-    );
+    let client = RpcClient::new(url);
+    println!("Running on: {}", client.url().green());
+    let payer = read_keypair_file(&keypair_path).expect("Failed to read keypair file");
+
+    for kind in 0..sol_xen_sdk::MAX_KINDS {
+        let Some(instruction) = sol_xen_sdk::legacy_import(&client, &program_id, &payer.pubkey(), kind) else {
+            println!("Kind={}: nothing to import", kind);
+            continue;
+        };
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+                instruction
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            client.get_latest_blockhash().unwrap(),
+        );
+        match client.send_and_confirm_transaction_with_spinner(&transaction) {
+            Ok(signature) => {
+                let (user_sol_xn_record_pda, _) = pda::user_sol_xn_record(&payer.pubkey(), kind, &program_id);
+                let points = get_account::<UserSolXnRecord>(&client, &user_sol_xn_record_pda)
+                    .map(|s| (s.points / sol_xen_sdk::DECIMALS).to_string())
+                    .unwrap_or(String::from("-"));
+                println!("Kind={}: Tx={}, points={}", kind, signature.to_string().yellow(), points.yellow());
+            },
+            Err(err) => println!("Kind={}: Failed: {:?}", kind, err),
+        }
+    }
+}
+
+// Reclaim the rent of user records of all kinds, once mining is over and their points are minted
+fn do_close(priority_fee: u64) {
+    let keypair_path = std::env::var("USER_WALLET").expect("USER_WALLET must be set.");
+    let url = std::env::var("ANCHOR_PROVIDER_URL").expect("ANCHOR_PROVIDER_URL must be set.");
+
+    let program_id_miner = sol_xen_sdk::miner_program_id();
+    let program_id_minter = sol_xen_sdk::minter_program_id();
+    println!("Miner Program ID={}", program_id_miner.to_string().green());
+
+    let client = RpcClient::new(url);
+    println!("Running on: {}", client.url().green());
+    let payer = read_keypair_file(&keypair_path).expect("Failed to read keypair file");
+
+    let mut reclaimed = 0;
+    for kind in 0..sol_xen_sdk::MAX_KINDS {
+        let (user_sol_xn_record_pda, _) = pda::user_sol_xn_record(&payer.pubkey(), kind, &program_id_miner);
+        let Some(instruction) = sol_xen_sdk::user_record_close(
+            &client, &program_id_miner, &program_id_minter, &payer.pubkey(), kind
+        ) else {
+            println!("Kind={}: nothing to close (no record, mining not over or points not minted)", kind);
+            continue;
+        };
+        let lamports = client.get_balance(&user_sol_xn_record_pda).unwrap_or(0);
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+                instruction
+            ],
+            Some(&payer.pubkey()),
+            &[&payer],
+            client.get_latest_blockhash().unwrap(),
+        );
+        match client.send_and_confirm_transaction_with_spinner(&transaction) {
+            Ok(signature) => {
+                reclaimed += lamports;
+                println!("Kind={}: Tx={}, reclaimed lamports={}", kind, signature.to_string().yellow(), lamports.to_string().yellow());
+            },
+            Err(err) => println!("Kind={}: Failed: {:?}", kind, err),
+        }
+    }
+    println!("Reclaimed lamports={}", reclaimed.to_string().green());
+}
 
-    let method_name_data = "global:mint_tokens";
-    let digest = hash(method_name_data.as_bytes());
-    let ix_data = &digest.to_bytes()[0..8];
-
-    let instruction = Instruction {
-        program_id: program_id_minter,
-        data: [
-            ix_data,
-            kind.to_be_bytes().as_slice()
-        ].concat().to_vec(),
-        accounts: vec![
-            AccountMeta::new_readonly(user_sol_xn_record_pda, false),
-            AccountMeta::new(user_token_record_pda, false),
-            AccountMeta::new(user_token_account, false),
-            AccountMeta::new_readonly(payer.pubkey(), true),
-            AccountMeta::new(mint_pda, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
-            AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(associate_token_program, false),
-            AccountMeta::new_readonly(program_id_miner, false)
-        ]
+// Minted solXEN supply, as reported by the minter's `get_supply_stats` view
+fn do_supply() {
+    let keypair_path = std::env::var("USER_WALLET").expect("USER_WALLET must be set.");
+    let url = std::env::var("ANCHOR_PROVIDER_URL").expect("ANCHOR_PROVIDER_URL must be set.");
+
+    let program_id_minter = sol_xen_sdk::minter_program_id();
+    println!("Program ID={}", program_id_minter.to_string().green());
+
+    let client = RpcClient::new(url);
+    println!("Running on: {}", client.url().green());
+    let payer = read_keypair_file(&keypair_path).expect("Failed to read keypair file");
+
+    let Some(stats) = sol_xen_sdk::fetch_supply_stats(&client, &program_id_minter, &payer.pubkey()) else {
+        println!("{}", "Supply is not tracked by the minter".red());
+        return;
     };
+    let tokens = |base_units: u128| (base_units as f64 / sol_xen_sdk::TOKEN_DECIMALS as f64).to_string();
+    println!(
+        "Minted={} Cap={} Remaining={} Mint supply={}",
+        tokens(stats.tokens_minted).green(),
+        if stats.cap == 0 { "none".to_string() } else { tokens(stats.cap) }.green(),
+        stats.remaining.map_or("unlimited".to_string(), tokens).green(),
+        tokens(stats.mint_supply as u128).green(),
+    );
+    for (kind, points) in stats.points_counters.iter().enumerate() {
+        println!("Kind={}: points converted={}", kind, (points / sol_xen_sdk::DECIMALS).to_string().yellow());
+    }
+}
+
+// Mint tokens based on provided evidence of mining points, of all kinds at once
+fn do_mint(priority_fee: u64) {
+    let keypair_path = std::env::var("USER_WALLET").expect("USER_WALLET must be set.");
+    let url = std::env::var("ANCHOR_PROVIDER_URL").expect("ANCHOR_PROVIDER_URL must be set.");
+
+    let program_id_minter = sol_xen_sdk::minter_program_id();
+    let program_id_miner = sol_xen_sdk::miner_program_id();
+
+    println!("Program ID={}", program_id_minter.to_string().green());
+    println!("Miner Program ID={}", program_id_miner.to_string().green());
+
+    let client = RpcClient::new(url);
+    println!("Running on: {}", client.url().green());
+    let payer = read_keypair_file(&keypair_path).expect("Failed to read keypair file");
+
+    println!("Using user wallet={}, fee={}", payer.pubkey().to_string().green(), priority_fee.to_string().green(), );
+
+    let (user_token_record_pda, _user_rec_bump) = pda::user_tokens_record(&payer.pubkey(), &program_id_minter);
+    println!("User token record PDA={} bump={}", user_token_record_pda.to_string().green(), _user_rec_bump);
+
+    let (mint_pda, _mint_bump) = pda::mint(&program_id_minter);
+    println!("Mint PDA={}", mint_pda.to_string().green());
+
+    // migrations and legacy imports go first, in txs of their own
+    match sol_xen_sdk::prepare_mint_all(&client, &program_id_miner, &payer, priority_fee) {
+        Ok(signatures) => signatures.iter().for_each(|signature| println!("Import Tx={}", signature.to_string().yellow())),
+        Err(err) => {
+            println!("Failed: {:?}", err);
+            return;
+        }
+    }
+
+    // points of all kinds are converted at once
+    let instructions = sol_xen_sdk::mint_all_instructions(&client, &program_id_miner, &program_id_minter, &payer.pubkey());
 
     // let compute_budget_instruction_limit = ComputeBudgetInstruction::set_compute_unit_limit(1_400_000);
     let compute_budget_instruction_price = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
 
     let transaction = Transaction::new_signed_with_payer(
         &[
-            // compute_budget_instruction_limit,
-            compute_budget_instruction_price,
-            instruction
-        ],
+            vec![
+                // compute_budget_instruction_limit,
+                compute_budget_instruction_price,
+            ],
+            instructions,
+        ].concat(),
         Some(&payer.pubkey()),
         &[&payer],
         client.get_latest_blockhash().unwrap(),
@@ -366,42 +505,30 @@ fn do_mint(priority_fee: u64, kind: u8) {
 
     match result {
         Ok(signature) => {
-
-            let maybe_user_account_data_raw = client.get_account_data(&user_sol_xn_record_pda);
-            match maybe_user_account_data_raw {
-                Ok(user_account_data_raw) => {
-                    // 36 32 28
-                    // println!("{} {}", user_account_data_raw.len(), size_of::<UserSolXnRecord>());
-                    let user_data: [u8; size_of::<UserSolXnRecord>() - 4] = user_account_data_raw.as_slice()[8..36].try_into().unwrap();
-                    let user_state = UserSolXnRecord::try_from_slice(user_data.as_ref()).unwrap();
+            println!("Tx={}", signature.to_string().yellow());
+            for (kind, user_record) in sol_xen_sdk::user_records_to_mint(&program_id_miner, &payer.pubkey()).iter().enumerate() {
+                if let Some(user_state) = get_account::<UserSolXnRecord>(&client, user_record) {
                     println!(
-                        "Tx={}, hashes={}, superhashes={}, points={}",
-                        signature.to_string().yellow(),
+                        "Kind={}: hashes={}, superhashes={}, points={}",
+                        kind,
                         user_state.hashes.to_string().yellow(),
                         user_state.superhashes.to_string().yellow(),
                         user_state.points.to_string().yellow(),
-                    )
+                    );
                 }
-                Err(_) => println!("Account data not yet ready; skipping")
             }
-            let maybe_user_balance_data_raw = client.get_account_data(&user_token_record_pda);
-            match maybe_user_balance_data_raw {
-                Ok(user_balance_data_raw) => {
-                    // 88 80
-                    // println!("{} {}", user_balance_data_raw.len(), size_of::<UserTokensRecord>());
-                    let user_data: [u8; size_of::<UserTokensRecord>()] = user_balance_data_raw.as_slice()[8..].try_into().unwrap();
-                    let user_state = UserTokensRecord::try_from_slice(user_data.as_ref()).unwrap();
-                    println!(
-                        "Points={:?} Tokens={}",
-                        user_state.points_counters,
-                        user_state.tokens_minted,
-                    )
-                }
-                Err(_) => println!("Account data not yet ready; skipping")
+            match get_account::<UserTokensRecord>(&client, &user_token_record_pda) {
+                Some(user_state) => println!(
+                    "Points={:?} Converted={} Tokens={} (base units={}, carried points={})",
+                    user_state.points_counters,
+                    user_state.points_converted,
+                    user_state.tokens_minted as f64 / sol_xen_sdk::TOKEN_DECIMALS as f64,
+                    user_state.tokens_minted,
+                    user_state.points_remainder,
+                ),
+                None => println!("Account data not yet ready; skipping")
             }
-
         },
         Err(err) => println!("Failed: {:?}", err),
     };
 }
-
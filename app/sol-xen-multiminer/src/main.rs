@@ -1,16 +1,11 @@
 use std::collections::HashMap;
-use std::mem::size_of;
 use std::ops::Sub;
 use solana_client::rpc_client::RpcClient;
 use solana_client::pubsub_client::{PubsubClient};
-use solana_sdk::hash::{hash};
-use solana_sdk::{system_program, pubkey::Pubkey, signature::{Signer, read_keypair_file}, transaction::Transaction, instruction::{Instruction, AccountMeta}, compute_budget::ComputeBudgetInstruction, system_instruction};
-use spl_token;
-use spl_associated_token_account::get_associated_token_address_with_program_id;
+use solana_sdk::{pubkey::Pubkey, signature::{Signer, read_keypair_file}, transaction::Transaction, compute_budget::ComputeBudgetInstruction, system_instruction};
 use clap::{Parser};
 use std::process;
 use std::sync::mpsc;
-use borsh::{BorshSerialize, BorshDeserialize, to_vec, BorshSchema};
 use ethaddr::Address;
 use colored::*;
 use dotenv::dotenv;
@@ -27,14 +22,16 @@ use spl_memo::build_memo;
 use serde::{Deserialize, Serialize};
 use futures::channel::oneshot;
 use url::{Url};
-
-const MINERS: &str = "B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN,2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ,5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ,DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S";
-
-const MINTER: &str = "EPAdVJ5S317jJr2ejgxoA52iptvphGXjPLbqXhZH4n8o";
-
-const MAX_MINERS: u8 = 4;
-
-const DECIMALS: u128 = 1_000_000_000;
+use sol_xen_sdk::{
+    pda,
+    get_account,
+    UserEthXnRecord,
+    UserSolXnRecord,
+    UserTokensRecord,
+    DECIMALS,
+    TOKEN_DECIMALS,
+    MAX_KINDS,
+};
 
 /*
     Color::Red => "31".into(),
@@ -48,18 +45,6 @@ const Y: &str = "\x1b[0;33m";
 // const B: &str = "\x1b[0;34m";
 const U: &str = "\x1b[0;39m";
 
-#[derive(BorshSerialize, Debug)]
-pub struct EthAccount {
-    pub address: [u8; 20],
-    pub address_str: String
-}
-
-#[derive(BorshSerialize, Debug)]
-pub struct MineHashes {
-    pub eth_account: EthAccount,
-    pub _kind: u8,
-}
-
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
@@ -71,8 +56,17 @@ struct Args {
     kind: u8,
     #[arg(short, long, default_value_t = 1)]
     fee: u64,
-    #[arg(short, long, default_value_t = 1_180_000)]
-    units: u32,
+    /// Compute unit limit per mine tx, derived from `iterations` if not set
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=sol_xen_sdk::MAX_TX_UNITS as i64))]
+    units: Option<u32>,
+    /// Hashes computed per mine tx, up to the kind's max_hashes and `sol_xen_sdk::MAX_ITERATIONS`
+    #[arg(
+        short,
+        long,
+        default_value_t = sol_xen_sdk::DEFAULT_ITERATIONS,
+        value_parser = clap::value_parser!(u8).range(1..=sol_xen_sdk::MAX_ITERATIONS as i64)
+    )]
+    iterations: u8,
     #[arg(short, long, default_value_t = 1)]
     runs: u32,
     #[arg(short, long, default_value_t = 0.5)]
@@ -83,43 +77,11 @@ struct Args {
     jito_tip: u64,
     #[arg(short, long)]
     wallet_path: Option<String>,
+    /// Wallet to consolidate the minted tokens of all id{kind}.json wallets in (set once per wallet, can't be changed)
+    #[arg(long)]
+    payout: Option<String>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone)]
-pub struct GlobalXnRecord {
-    pub amp: u16,
-    pub last_amp_slot: u64,
-    pub nonce: [u8; 4],
-    pub kind: u8,
-    pub hashes: u64,
-    pub superhashes: u32,
-    pub points: u128
-} // 38 <> 48
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone)]
-pub struct BoxedGlobalXnRecord {
-    pub data: Box<GlobalXnRecord>
-}
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
-pub struct UserEthXnRecord {
-    pub hashes: u64,  // 8
-    pub superhashes: u32, // 4
-} // 16 == 16
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone)]
-pub struct UserSolXnRecord {
-    pub hashes: u64, // 8
-    pub superhashes: u32, // 4
-    pub points: u128, // 16
-} // 28
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone)]
-pub struct UserTokensRecord {
-    pub points_counters: [u128; 4], // 4 * 16 = 64
-    pub tokens_minted: u128 // 16
-} // 80
-
 pub struct MineParams {
     ethereum_address: String,
     address: [u8; 20],
@@ -128,6 +90,7 @@ pub struct MineParams {
     kind: u8,
     delay: f32,
     units: u32,
+    iterations: u8,
     jito_tip: Option<u64>,
     tippers: Vec<String>,
 }
@@ -157,8 +120,8 @@ pub struct JitoBundleStatus {
     value: Option<Vec<JitoValue>>,
 }
 
-impl From<MineParams> for (String, [u8; 20], u64, u32, u8, f32, u32, Option<u64>, Vec<String>) {
-    fn from(x: MineParams) -> (String, [u8; 20], u64, u32, u8, f32, u32, Option<u64>, Vec<String>) {
+impl From<MineParams> for (String, [u8; 20], u64, u32, u8, f32, u32, u8, Option<u64>, Vec<String>) {
+    fn from(x: MineParams) -> (String, [u8; 20], u64, u32, u8, f32, u32, u8, Option<u64>, Vec<String>) {
         let MineParams {
             ethereum_address,
             address,
@@ -167,10 +130,11 @@ impl From<MineParams> for (String, [u8; 20], u64, u32, u8, f32, u32, Option<u64>
             kind,
             delay,
             units,
+            iterations,
             jito_tip,
             tippers,
         } = x;
-        (ethereum_address, address, priority_fee, runs, kind, delay, units, jito_tip, tippers)
+        (ethereum_address, address, priority_fee, runs, kind, delay, units, iterations, jito_tip, tippers)
     }
 }
 
@@ -179,12 +143,13 @@ pub struct MintParams {
     priority_fee: u64,
     kind: u8,
     automint: u32,
+    payout: Option<Pubkey>,
 }
 
-impl From<MintParams> for (u64, u64, u8, u32) {
-    fn from(x: MintParams) -> (u64, u64, u8, u32) {
-        let MintParams { slot, priority_fee, kind, automint } = x;
-        (slot, priority_fee, kind, automint)
+impl From<MintParams> for (u64, u64, u8, u32, Option<Pubkey>) {
+    fn from(x: MintParams) -> (u64, u64, u8, u32, Option<Pubkey>) {
+        let MintParams { slot, priority_fee, kind, automint, payout } = x;
+        (slot, priority_fee, kind, automint, payout)
     }
 }
 
@@ -198,13 +163,16 @@ async fn main() {
     let runs = args.runs;
     // let kind = args.kind;
     let delay = args.delay;
-    let units = args.units;
+    let iterations = args.iterations;
+    let units = args.units.unwrap_or(sol_xen_sdk::mine_hashes_units(iterations));
     let keypair_path = args.wallet_path
         .or(std::env::var("USER_WALLET_PATH").ok())
         .expect("Either set USER_WALLET_PATH env var, or pass it as -w command line param");
     // let command = &args.command[..];
     let automint = args.automint;
     let jito_tip = if args.jito_tip > 0 { Some(args.jito_tip) } else { None };
+    let payout = args.payout
+        .map(|s| Pubkey::try_from(s.trim()).expect("Bad payout wallet"));
 
     // Use ethaddr to parse and validate the Ethereum address with checksum
     let _address = match Address::from_str_checksum(&ethereum_address) {
@@ -220,7 +188,7 @@ async fn main() {
     let ws_url_ =  str::replace(url.as_str(), "http", "ws");
     let ws_url =  str::replace(ws_url_.as_str(), "8899", "8900");
     let jito_url = if jito_tip.is_some() {
-        std::env::var("JITO_PROVIDER_URL").map(|u| Some(u))
+        std::env::var("JITO_PROVIDER_URL").map(Some)
             .expect("JITO_PROVIDER_URL must be set with 'jito_tip' param.")
     } else { None };
 
@@ -237,65 +205,64 @@ async fn main() {
     let (tx, rx) = mpsc::channel::<String>();
     let mut wallets: HashMap<u8, Keypair> = HashMap::new();
     let tx_clone = tx.clone();
-    for kind in 0..MAX_MINERS {
+    for kind in 0..MAX_KINDS {
         let keypair_path_norm = if keypair_path.ends_with("/")
         { keypair_path.clone() } else { keypair_path.clone() + "/" };
         let keypair_fn = format!("{keypair_path_norm}id{kind}.json");
-        let _ = match read_keypair_file(&keypair_fn) {
-            Ok(keypair) => {
-                wallets.insert(kind, keypair.insecure_clone());
-                let a = ethereum_address.clone();
-                let txc = tx_clone.clone();
-                let tt = tippers.clone();
-                let keypair_clone = keypair.insecure_clone();
-                let _h = thread::spawn(move || {
-                    do_mine(
-                        keypair,
-                        MineParams {
-                            ethereum_address: a,
-                            address: *_address,
-                            priority_fee,
-                            runs,
-                            kind,
-                            delay,
-                            units,
-                            jito_tip,
-                            tippers: tt
-                        },
-                        txc)
-                });
-                // h.join().unwrap();
-                if automint > 0 {
-                    let tx_clone1 = tx.clone();
-                    let ws = ws_url.clone();
-                    let _hm = thread::spawn(move || {
-                        let mut last_slot: Slot = 0;
-                        let _ = match PubsubClient::slot_subscribe(ws.as_str()) {
-                            Ok(subs) => {
-                                for slot in subs.1 {
-                                    let txcm = tx_clone1.clone();
-                                    let kpm = keypair_clone.insecure_clone();
-                                    if slot.slot.ge(&last_slot) && slot.slot.sub(last_slot).ge(&(automint as u64)) {
-                                        last_slot = slot.slot;
-                                        do_mint(
-                                            kpm,
-                                            MintParams {
-                                                slot: slot.slot,
-                                                priority_fee,
-                                                kind,
-                                                automint
-                                            },
-                                            txcm
-                                        )
-                                    }
+        if let Ok(keypair) = read_keypair_file(&keypair_fn) {
+            wallets.insert(kind, keypair.insecure_clone());
+            let a = ethereum_address.clone();
+            let txc = tx_clone.clone();
+            let tt = tippers.clone();
+            let keypair_clone = keypair.insecure_clone();
+            let _h = thread::spawn(move || {
+                do_mine(
+                    keypair,
+                    MineParams {
+                        ethereum_address: a,
+                        address: *_address,
+                        priority_fee,
+                        runs,
+                        kind,
+                        delay,
+                        units,
+                        iterations,
+                        jito_tip,
+                        tippers: tt
+                    },
+                    txc)
+            });
+            // h.join().unwrap();
+            if automint > 0 {
+                let tx_clone1 = tx.clone();
+                let ws = ws_url.clone();
+                let _hm = thread::spawn(move || {
+                    let mut last_slot: Slot = 0;
+                    match PubsubClient::slot_subscribe(ws.as_str()) {
+                        Ok(subs) => {
+                            for slot in subs.1 {
+                                let txcm = tx_clone1.clone();
+                                let kpm = keypair_clone.insecure_clone();
+                                if slot.slot.ge(&last_slot) && slot.slot.sub(last_slot).ge(&(automint as u64)) {
+                                    last_slot = slot.slot;
+                                    do_mint(
+                                        kpm,
+                                        MintParams {
+                                            slot: slot.slot,
+                                            priority_fee,
+                                            kind,
+                                            automint,
+                                            payout,
+                                        },
+                                        txcm
+                                    )
                                 }
                             }
-                            Err(e) => { println!("{:?}", e) }
-                        };
-                    });
-                }
-            },
-            _ => ()
+                        }
+                        Err(e) => { println!("{:?}", e) }
+                    };
+                });
+            }
         };
     }
 
@@ -308,10 +275,11 @@ async fn main() {
         automint.to_string().green(),
     );
     println!(
-        "Running on RPC={}, fee={}, units={}",
+        "Running on RPC={}, fee={}, units={}, iterations={}",
         url.green(),
         priority_fee.to_string().green(),
         units.to_string().green(),
+        iterations.to_string().green(),
     );
 
     for msg in rx {
@@ -319,56 +287,18 @@ async fn main() {
     }
 }
 
-fn get_eth_record(client: &RpcClient, pda: &Pubkey) -> Option<UserEthXnRecord> {
-    let maybe_user_account_data_raw = client.get_account_data(&pda);
-    match maybe_user_account_data_raw {
-        Ok(user_account_data_raw) => {
-            let user_data: [u8; size_of::<UserEthXnRecord>() - 4] = user_account_data_raw.as_slice()[8..20].try_into().unwrap();
-            Some(UserEthXnRecord::try_from_slice(user_data.as_ref()).unwrap())
-        }
-        Err(_) => None
-    }
-}
-
-fn get_sol_record(client: &RpcClient, pda: &Pubkey) -> Option<UserSolXnRecord> {
-    let maybe_user_sol_account_data_raw = client.get_account_data(&pda);
-    match maybe_user_sol_account_data_raw {
-        Ok(user_sol_account_data_raw) => {
-            // 36 32 28
-            // println!("{} {}", user_sol_account_data_raw.len(), size_of::<UserSolXnRecord>());
-            let user_sol_data: [u8; size_of::<UserSolXnRecord>() - 4] = user_sol_account_data_raw.as_slice()[8..].try_into().unwrap();
-            Some(UserSolXnRecord::try_from_slice(user_sol_data.as_ref()).unwrap())
-        }
-        Err(_) => None
-    }
-}
-
-fn get_token_record(client: &RpcClient, pda: &Pubkey) -> Option<UserTokensRecord> {
-    let maybe_user_token_account_data_raw = client.get_account_data(&pda);
-    match maybe_user_token_account_data_raw {
-        Ok(user_balance_data_raw) => {
-            let user_data: [u8; size_of::<UserTokensRecord>()] = user_balance_data_raw.as_slice()[8..].try_into().unwrap();
-            Some(UserTokensRecord::try_from_slice(user_data.as_ref()).unwrap())
-        }
-        Err(_) => None
-    }
-}
-
-
 async fn wait_for_status(sig: String, jito_client: HttpClient) -> oneshot::Receiver<JitoBundleStatus> {
     let params1 = rpc_params![[sig]];
     let (sender, receiver) = oneshot::channel();
     async {
         loop {
             let r: Result<JitoBundleStatus, _> = jito_client.request("getBundleStatuses", params1.clone()).await;
-            if r.is_ok() {
-                let res = r.unwrap();
-                if res.value.is_some() {
-                    if res.clone().value.unwrap().len() > 0 {
+            if let Ok(res) = r {
+                if res.value.is_some()
+                    && !res.clone().value.unwrap().is_empty() {
                         sender.send(res).unwrap();
                         return;
                     }
-                }
             }
             thread::sleep(Duration::from_millis(1_000));
         }
@@ -387,6 +317,7 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
         kind,
         delay,
         units,
+        iterations,
         jito_tip,
         tippers,
     ) = params.into();
@@ -403,85 +334,48 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
     let jito_bundles_url = jito_base_url
             .map(|u|format!("{}/bundles", u));
 
-    let miners_program_ids_str= std::env::var("MINERS").unwrap_or(String::from(MINERS));
-    let miners = miners_program_ids_str.split(',').collect::<Vec<&str>>();
-    assert_eq!(miners.len(), MAX_MINERS as usize, "Bad miners set");
-
-    let program_id = Pubkey::try_from(miners[kind as usize]).expect("Bad program ID");
+    let program_id = sol_xen_sdk::miner_program_id();
 
-    tx.send(format!("{Y}[{}]{U} Miner Program ID={}", kind.to_string(), program_id.to_string().green())).unwrap();
+    tx.send(format!("{Y}[{}]{U} Miner Program ID={}", kind, program_id.to_string().green())).unwrap();
 
     let client = RpcClient::new(url.clone());
     let rpc_client = RpcClient::new(url.clone());
 
     tx.send(format!(
         "{Y}[{}]{U} Using user wallet={}, account={}",
-        kind.to_string(),
+        kind,
         payer.pubkey().to_string().green(),
         ethereum_address.green(),
     )).unwrap();
 
-    let (global_xn_record_pda, _global_bump) = Pubkey::find_program_address(
-        &[b"xn-miner-global", kind.to_be_bytes().as_slice()],
-        &program_id
-    );
-    // tx.send(format!("Global XN PDA: {}", global_xn_record_pda.to_string().green())).unwrap();
-
-    let (user_eth_xn_record_pda, _user_eth_bump) = Pubkey::find_program_address(
-        &[
-            b"xn-by-eth",
-            &address.as_slice(),
-            kind.to_be_bytes().as_slice(),
-            program_id.as_ref()
-        ],
-        &program_id
-    );
+    let (user_eth_xn_record_pda, _user_eth_bump) = pda::user_eth_xn_record(&address, kind, &program_id);
     // tx.send(format!("User Eth PDA: {}", user_eth_xn_record_pda.to_string().green())).unwrap();
 
-    let (user_sol_xn_record_pda, _user_sol_bump) = Pubkey::find_program_address(
-        &[
-            b"xn-by-sol",
-            &payer.pubkey().to_bytes(),
-            kind.to_be_bytes().as_slice(),
-            program_id.as_ref()
-        ],
-        &program_id
-    );
+    let (user_sol_xn_record_pda, _user_sol_bump) = pda::user_sol_xn_record(&payer.pubkey(), kind, &program_id);
     // tx.send(format!("User Sol PDA: {}", user_sol_xn_record_pda.to_string().green())).unwrap();
 
-    let method_name_data = "global:mine_hashes";
-    let digest = hash(method_name_data.as_bytes());
-    let ix_data  = &digest.to_bytes()[0..8];
+    let mining_is_over = || sol_xen_sdk::fetch_remaining_cycles(&client, kind, &program_id) == Some(0);
+    if mining_is_over() {
+        tx.send(format!("{Y}[{}]{U} {}", kind, "Mining is over; nothing to do".red())).unwrap();
+        return;
+    }
 
     let mut _run = 0;
     while runs == 0 || _run < runs  {
         _run += 1;
-        let address_str = ethereum_address.clone();
-
-        let mint_hashes = MineHashes {
-            eth_account: EthAccount {
-                address,
-                address_str
-            },
-            _kind: kind
-        };
 
         let compute_budget_instruction_limit = ComputeBudgetInstruction::set_compute_unit_limit(units);
         let compute_budget_instruction_price = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
 
-        let mine_instruction = Instruction {
-            program_id,
-            data: [ix_data, to_vec(&mint_hashes).unwrap().as_slice()].concat().to_vec(),
-            accounts: vec![
-                AccountMeta::new(global_xn_record_pda, false),
-                AccountMeta::new(user_eth_xn_record_pda, false),
-                AccountMeta::new(user_sol_xn_record_pda, false),
-                AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new_readonly(system_program::ID, false),
-            ],
-        };
+        let mine_instruction = sol_xen_sdk::mine_hashes_v2(
+            &program_id,
+            &payer.pubkey(),
+            address,
+            kind,
+            iterations
+        );
 
-        if jito_tip.is_some() && tippers.len() > 0 {
+        if jito_tip.is_some() && !tippers.is_empty() {
             // tx.send(format!("{Y}[{}]{U} Using Jito URL={}", kind.to_string(), jito_bundles_url.clone().unwrap().green())).unwrap();
 
             let jito_client: HttpClient = HttpClientBuilder::default().build(jito_bundles_url.clone().unwrap()).expect("Error");
@@ -489,7 +383,7 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
             let tip_jito = system_instruction::transfer(
                     &payer.pubkey(),
                     &Pubkey::try_from(tippers[0].to_string().as_str()).unwrap(),
-                    jito_tip.map(|t|t).unwrap_or(1)
+                    jito_tip.unwrap_or(1)
                 );
 
             let transactions = (0..5).map(|i| {
@@ -515,31 +409,33 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
             let params = rpc_params![transactions];
             
             let resp: Result<String, _> = jito_client.request("sendBundle", params).await;
-            if resp.is_ok() {
-                let sig = resp.expect("err");
+            if let Ok(sig) = resp {
                 tx.send(format!("{Y}[{}]{U} Bundle ID={}", kind, sig.yellow())).unwrap();
 
                 let status = wait_for_status(sig, jito_client).await;
                 let result = status.await;
-                if result.is_ok() {
-                    result.unwrap().value.map(|v| v[0].transactions.clone()).unwrap()
+                if let Ok(result) = result {
+                    result.value.map(|v| v[0].transactions.clone()).unwrap()
                         .iter()
                         .for_each(|hash|tx.send(format!("{Y}[{}]{U}   Tx={}", kind, hash.yellow())).unwrap());
                     
-                    let user_state = get_eth_record(&client, &user_eth_xn_record_pda);
-                    let user_sol_state = get_sol_record(&client, &user_sol_xn_record_pda);
+                    let user_state = get_account::<UserEthXnRecord>(&client, &user_eth_xn_record_pda);
+                    let user_sol_state = get_account::<UserSolXnRecord>(&client, &user_sol_xn_record_pda);
                     let (h, sh) = user_state
                         .map(|s| (s.hashes.to_string(), s.superhashes.to_string()))
                         .unwrap_or((String::from("-"), String::from("-")));
                     tx.send(format!(
                         "{Y}[{}]{U} Hashes={}, Superhashes={}, Points={}",
-                        kind.to_string(),
+                        kind,
                         h.yellow(),
                         sh.yellow(),
                         user_sol_state
                             .map(|s| (s.points / DECIMALS).to_string())
                             .unwrap_or(String::from("-")).yellow(),
                     )).unwrap();
+                } else if mining_is_over() {
+                    tx.send(format!("{Y}[{}]{U} {}", kind, "Mining is over; stopping".red())).unwrap();
+                    break;
                 } else {
                     tx.send(format!("{Y}[{}]{U} Error in bundle processing; skipping...", kind)).unwrap();
                 }
@@ -563,13 +459,13 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
             let result = rpc_client.send_transaction(&transaction);
             match result {
                 Ok(signature) => {
-                    let user_state = get_eth_record(&client, &user_eth_xn_record_pda);
-                    let user_sol_state = get_sol_record(&client, &user_sol_xn_record_pda);
+                    let user_state = get_account::<UserEthXnRecord>(&client, &user_eth_xn_record_pda);
+                    let user_sol_state = get_account::<UserSolXnRecord>(&client, &user_sol_xn_record_pda);
                     let (h, sh) = user_state.map(|s| (s.hashes.to_string(), s.superhashes.to_string()))
                         .unwrap_or((String::from("-"), String::from("-")));
                     tx.send(format!(
                         "{Y}[{}]{U} Tx={}, hashes={}, superhashes={}, points={}, url={}",
-                        kind.to_string(),
+                        kind,
                         signature.to_string().yellow(),
                         h.yellow(),
                         sh.yellow(),
@@ -579,6 +475,10 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
                     )).unwrap();
                     thread::sleep(Duration::from_secs_f32(delay));
                 },
+                Err(err) if sol_xen_sdk::is_mining_over_error(&err) => {
+                    tx.send(format!("{Y}[{}]{U} {}", kind, "Mining is over; stopping".red())).unwrap();
+                    break;
+                },
                 Err(err) => tx.send(format!("Failed: {:?}", err)).unwrap(),
             };
         }
@@ -587,17 +487,11 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
 
 // Mint tokens based on provided evidence of mining points
 fn do_mint(payer: Keypair, params: MintParams, tx: mpsc::Sender<String>) {
-    let (slot, priority_fee, kind, _) = params.into();
+    let (slot, priority_fee, kind, _, payout) = params.into();
     let url = std::env::var("ANCHOR_PROVIDER_URL").expect("ANCHOR_PROVIDER_URL must be set.");
 
-    let program_id_minter_str = std::env::var("PROGRAM_ID_MINTER").unwrap_or(String::from(MINTER));
-    let program_id_minter = Pubkey::try_from(program_id_minter_str.as_str()).expect("Bad program ID");
-
-    let miners_program_ids_str= std::env::var("MINERS").unwrap_or(String::from(MINERS));
-    let miners = miners_program_ids_str.split(',').collect::<Vec<&str>>();
-    assert_eq!(miners.len(), MAX_MINERS as usize, "Bad miners set");
-
-    let program_id_miner = Pubkey::try_from(miners[kind as usize]).expect("Bad program ID");
+    let program_id_minter = sol_xen_sdk::minter_program_id();
+    let program_id_miner = sol_xen_sdk::miner_program_id();
 
     // println!("Test{:?}", kind.to_be_bytes().as_slice());
     // tx.send(format!(
@@ -610,74 +504,49 @@ fn do_mint(payer: Keypair, params: MintParams, tx: mpsc::Sender<String>) {
 
     // println!("Using user wallet={}, fee={}", payer.pubkey().to_string().green(), priority_fee.to_string().green(), );
 
-    let (user_sol_xn_record_pda, _user_bump) = Pubkey::find_program_address(
-        &[
-            b"xn-by-sol",
-            &payer.pubkey().to_bytes(),
-            kind.to_be_bytes().as_slice(),
-            &program_id_miner.to_bytes()
-        ],
-        &program_id_miner
-    );
-    // println!("User record PDA={} bump={}", user_sol_xn_record_pda.to_string().green(), _user_bump.to_string());
-
-    let (user_token_record_pda, _user_rec_bump) = Pubkey::find_program_address(
-        &[
-            b"sol-xen-minted",
-            &payer.pubkey().to_bytes(),
-        ],
-        &program_id_minter
-    );
+    let (user_token_record_pda, _user_rec_bump) = pda::user_tokens_record(&payer.pubkey(), &program_id_minter);
     // println!("User token record PDA={} bump={}", user_token_record_pda.to_string().green(), _user_rec_bump.to_string());
 
-    let (mint_pda, _mint_bump) = Pubkey::find_program_address(
-        &[b"mint"],
-        &program_id_minter
-    );
-    // println!("Mint PDA={}", mint_pda.to_string().green());
-
-    let associate_token_program = Pubkey::try_from("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
+    // payout is set in a tx of its own: the mint tx has to be built with the recipient
+    if let Some(recipient) = payout {
+        if let Some(instruction) = sol_xen_sdk::payout_setup(&client, &program_id_minter, &payer.pubkey(), &recipient) {
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer],
+                client.get_latest_blockhash().unwrap(),
+            );
+            match client.send_and_confirm_transaction(&transaction) {
+                Ok(signature) => tx.send(format!("{G}[{}]{U} Payout to {} set, Tx={}", kind, recipient, signature)).unwrap(),
+                Err(_err) => tx.send(format!("{R}[{}]{U} Unable to set payout", kind)).unwrap(),
+            }
+        } else if sol_xen_sdk::payout_recipient(&client, &program_id_minter, &payer.pubkey()) != recipient {
+            tx.send(format!("{R}[{}]{U} Payout was set to another wallet before; minting to it", kind)).unwrap();
+        }
+    }
 
-    let user_token_account = get_associated_token_address_with_program_id(
-        &payer.pubkey(),
-        &mint_pda,
-        &spl_token::ID
-    );
+    // migrations and legacy imports go first, in txs of their own
+    if let Err(err) = sol_xen_sdk::prepare_mint_all(&client, &program_id_miner, &payer, priority_fee) {
+        tx.send(format!("{R}[{}]{U} Unable to import legacy points: {:?}", kind, err)).unwrap();
+        return;
+    }
 
-    let method_name_data = "global:mint_tokens";
-    let digest = hash(method_name_data.as_bytes());
-    let ix_data = &digest.to_bytes()[0..8];
-
-    let instruction = Instruction {
-        program_id: program_id_minter,
-        data: [
-            ix_data,
-            kind.to_be_bytes().as_slice()
-        ].concat().to_vec(),
-        accounts: vec![
-            AccountMeta::new_readonly(user_sol_xn_record_pda, false),
-            AccountMeta::new(user_token_record_pda, false),
-            AccountMeta::new(user_token_account, false),
-            AccountMeta::new_readonly(payer.pubkey(), true),
-            AccountMeta::new(mint_pda, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
-            AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(associate_token_program, false),
-            AccountMeta::new_readonly(program_id_miner, false)
-        ]
-    };
+    // the wallet's points of all kinds are converted at once
+    let instructions = sol_xen_sdk::mint_all_instructions(&client, &program_id_miner, &program_id_minter, &payer.pubkey());
 
     // get pre-tx user balance
-    let user_token_state_pre = get_token_record(&client, &user_token_record_pda);
+    let user_token_state_pre = get_account::<UserTokensRecord>(&client, &user_token_record_pda);
     
     let compute_budget_instruction_price = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
     
     let transaction = Transaction::new_signed_with_payer(
         &[
-            // compute_budget_instruction_limit,
-            compute_budget_instruction_price,
-            instruction.clone()
-        ],
+            vec![
+                // compute_budget_instruction_limit,
+                compute_budget_instruction_price,
+            ],
+            instructions,
+        ].concat(),
         Some(&payer.pubkey()),
         &[&payer],
         client.get_latest_blockhash().unwrap(),
@@ -687,28 +556,33 @@ fn do_mint(payer: Keypair, params: MintParams, tx: mpsc::Sender<String>) {
 
     match result {
         Ok(signature) => {
-            // let user_sol_state = get_sol_record(&client, &user_sol_xn_record_pda);
-            let user_token_state = get_token_record(&client, &user_token_record_pda);
+            // let user_sol_state = get_account::<UserSolXnRecord>(&client, &user_sol_xn_record_pda);
+            let user_token_state = get_account::<UserTokensRecord>(&client, &user_token_record_pda);
             let user_token_state_1 = user_token_state.clone();
             let user_token_state_2 = user_token_state.clone();
             let delta = user_token_state_pre
                 .map(|s_pre| user_token_state
                     .map(|s| s.tokens_minted - s_pre.tokens_minted)
                 ).unwrap_or(Some(0)).unwrap_or(0);
-            let delta_str: String = if delta > 0 { format!("(+{})", delta / DECIMALS) } else { String::from("") };
+            let delta_str: String = if delta > 0 { format!("(+{})", delta as f64 / TOKEN_DECIMALS as f64) } else { String::from("") };
             let points = user_token_state_1
                 .map(|s| s.points_counters
                     .map(|p| (p / DECIMALS).to_string()).join(",")).unwrap_or(String::from(""));
 
+            let converted = user_token_state_2.as_ref()
+                .map(|s| (s.points_converted / DECIMALS).to_string())
+                .unwrap_or(String::from(""));
+
             // AM #2: balance @slot=150279: points=0,3600,3600,0, tokens=7200(+5400). Total supply=7200
             tx.send(format!(
-                "{G}[{}]{U} Mint Tx={}, Slot={} Points={} Tokens={}{}",
-                kind.to_string(),
+                "{G}[{}]{U} Mint Tx={}, Slot={} Points={} Converted={} Tokens={}{}",
+                kind,
                 signature.to_string().green(),
                 slot.to_string().green(),
                 points.green(),
+                converted.green(),
                 user_token_state_2
-                    .map(|s| (s.tokens_minted / DECIMALS).to_string())
+                    .map(|s| (s.tokens_minted as f64 / TOKEN_DECIMALS as f64).to_string())
                     .unwrap_or(String::from("")).green(),
                 delta_str.yellow(),
             )).unwrap()
@@ -716,7 +590,7 @@ fn do_mint(payer: Keypair, params: MintParams, tx: mpsc::Sender<String>) {
         },
         Err(_err) => tx.send(format!(
             "{R}[{}]{U} Unable to confirm Mint tx due to timeout",
-            kind.to_string()
+            kind
         )).unwrap(),
     };
 
@@ -0,0 +1,316 @@
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{hash::hash, pubkey::Pubkey};
+use std::io::{Error, ErrorKind, Result};
+
+/// Anchor account discriminator: first 8 bytes of sha256("account:<Name>")
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    hash(format!("account:{name}").as_bytes()).to_bytes()[0..8]
+        .try_into()
+        .unwrap()
+}
+
+/// Account owned by one of the solXEN programs, stored as Anchor discriminator + borsh data
+pub trait XnAccount: BorshDeserialize {
+    const NAME: &'static str;
+
+    /// Decodes the data (past the discriminator) of an older, unversioned layout, see `RECORD_VERSION`
+    fn upgrade(_data: &[u8]) -> Option<Self> {
+        None
+    }
+
+    fn try_from_account_data(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[0..8] != account_discriminator(Self::NAME) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Not a {} account", Self::NAME),
+            ));
+        }
+        Self::deserialize(&mut &data[8..]).or_else(|err| Self::upgrade(&data[8..]).ok_or(err))
+    }
+}
+
+/// Same as `sol_xen_miner::RECORD_VERSION`: records decoded from older layouts report 0 or 1
+pub const RECORD_VERSION: u8 = 2;
+
+/// True if the account holds a record in an older layout: a legacy miner's record, or a tokens record to be
+/// upgraded with `migrate_tokens_record`
+pub fn is_legacy_record<T: XnAccount>(data: &[u8]) -> bool {
+    data.len() > 8
+        && data[0..8] == account_discriminator(T::NAME)
+        && T::try_from_slice(&data[8..]).is_err()
+        && T::upgrade(&data[8..]).is_some()
+}
+
+/// Fetch and decode an account; `None` if it doesn't exist yet or can't be decoded
+pub fn get_account<T: XnAccount>(client: &RpcClient, address: &Pubkey) -> Option<T> {
+    client
+        .get_account_data(address)
+        .ok()
+        .and_then(|data| T::try_from_account_data(&data).ok())
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct MinerConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+impl XnAccount for MinerConfig {
+    const NAME: &'static str = "MinerConfig";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct MinterConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub miners: Vec<MinerEntry>,
+}
+
+impl XnAccount for MinterConfig {
+    const NAME: &'static str = "MinterConfig";
+}
+
+impl MinterConfig {
+    /// Same as `sol_xen_minter::MinterConfig::is_allowed`
+    pub fn is_allowed(&self, kind: u8, program_id: &Pubkey) -> bool {
+        self.miners.iter().any(|entry| entry.kind == kind && entry.program_id == *program_id)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq)]
+pub struct MinerEntry {
+    pub kind: u8,
+    pub program_id: Pubkey,
+    pub frozen: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct MinerParams {
+    pub version: u8,
+    pub kind: u8,
+    pub max_hashes: u8,
+    pub hash_pattern: String,
+    pub superhash_pattern: String,
+    pub superhash_x: u16,
+    pub amp_start: u16,
+    pub amp_cycle_slots: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    /// Decoded as `false` from the zeroed tail of version 1 params, see `sol_xen_miner::MINER_PARAMS_VERSION`
+    pub mix_slot_hash: bool,
+}
+
+impl XnAccount for MinerParams {
+    const NAME: &'static str = "MinerParams";
+}
+
+impl MinerParams {
+    /// Same as `sol_xen_miner::MinerParams::cycle_slots`
+    pub fn cycle_slots(&self) -> u64 {
+        self.amp_cycle_slots.saturating_add(1)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct GlobalXnRecord {
+    pub version: u8,
+    pub amp: u16,
+    pub last_amp_slot: u64,
+    pub nonce: [u8; 4],
+    pub kind: u8,
+    pub hashes: u64,
+    pub superhashes: u64,
+    pub points: u128,
+}
+
+impl XnAccount for GlobalXnRecord {
+    const NAME: &'static str = "GlobalXnRecord";
+
+    fn upgrade(data: &[u8]) -> Option<Self> {
+        let (amp, last_amp_slot, nonce, kind) = <(u16, u64, [u8; 4], u8)>::deserialize(&mut &data[..]).ok()?;
+        let rest = &data[15..];
+        let (version, hashes, superhashes, points) = match data.len() {
+            43 => <(u64, u32, u128)>::try_from_slice(rest).map(|(h, sh, p)| (0, h, sh as u64, p)).ok()?,
+            47 => <(u64, u64, u128)>::try_from_slice(rest).map(|(h, sh, p)| (1, h, sh, p)).ok()?,
+            _ => return None,
+        };
+        Some(GlobalXnRecord { version, amp, last_amp_slot, nonce, kind, hashes, superhashes, points })
+    }
+}
+
+impl GlobalXnRecord {
+    /// Same as `sol_xen_miner::GlobalXnRecord::pending_decays`: AMP cycles not stored by `crank_totals` yet
+    pub fn pending_decays(&self, slot: u64, params: &MinerParams) -> u64 {
+        slot.saturating_sub(self.last_amp_slot) / params.cycle_slots()
+    }
+
+    /// Same as `sol_xen_miner::GlobalXnRecord::amp_at`: AMP `mine_hashes` uses in `slot`
+    pub fn amp_at(&self, slot: u64, params: &MinerParams) -> u16 {
+        let decays = self.pending_decays(slot, params).min(self.amp as u64) as u16;
+        self.amp - decays
+    }
+
+    /// Same as `sol_xen_miner::GlobalXnRecord::remaining_cycles`; 0 means mining is over
+    pub fn remaining_cycles(&self, slot: u64, params: &MinerParams) -> u64 {
+        let by_amp = self.amp_at(slot, params) as u64;
+        if params.end_slot == 0 {
+            return by_amp;
+        }
+        if slot >= params.end_slot {
+            return 0;
+        }
+        by_amp.min((params.end_slot - slot).div_ceil(params.cycle_slots()))
+    }
+}
+
+/// Hashes mined with a shard since its last `crank_totals`, and the nonce its next `mine_hashes` uses
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct GlobalXnShard {
+    pub version: u8,
+    pub kind: u8,
+    pub shard: u8,
+    pub nonce: [u8; 4],
+    pub hashes: u64,
+    pub superhashes: u64,
+    pub points: u128,
+}
+
+impl XnAccount for GlobalXnShard {
+    const NAME: &'static str = "GlobalXnShard";
+}
+
+/// Return data of `sol_xen_miner::get_mining_status`
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct MiningStatus {
+    pub kind: u8,
+    pub slot: u64,
+    pub amp: u16,
+    pub end_slot: u64,
+    pub remaining_cycles: u64,
+    pub is_over: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct UserEthXnRecord {
+    pub version: u8,
+    pub hashes: u64,
+    pub superhashes: u64,
+}
+
+impl XnAccount for UserEthXnRecord {
+    const NAME: &'static str = "UserEthXnRecord";
+
+    fn upgrade(data: &[u8]) -> Option<Self> {
+        let (version, hashes, superhashes) = match data.len() {
+            12 => <(u64, u32)>::try_from_slice(data).map(|(h, sh)| (0, h, sh as u64)).ok()?,
+            16 => <(u64, u64)>::try_from_slice(data).map(|(h, sh)| (1, h, sh)).ok()?,
+            _ => return None,
+        };
+        Some(UserEthXnRecord { version, hashes, superhashes })
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct UserSolXnRecord {
+    pub version: u8,
+    pub hashes: u64,
+    pub superhashes: u64,
+    pub points: u128,
+}
+
+impl XnAccount for UserSolXnRecord {
+    const NAME: &'static str = "UserSolXnRecord";
+
+    fn upgrade(data: &[u8]) -> Option<Self> {
+        let (version, hashes, superhashes, points) = match data.len() {
+            28 => <(u64, u32, u128)>::try_from_slice(data).map(|(h, sh, p)| (0, h, sh as u64, p)).ok()?,
+            32 => <(u64, u64, u128)>::try_from_slice(data).map(|(h, sh, p)| (1, h, sh, p)).ok()?,
+            _ => return None,
+        };
+        Some(UserSolXnRecord { version, hashes, superhashes, points })
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct LegacyImport {
+    pub legacy_program: Pubkey,
+    pub hashes: u64,
+    pub superhashes: u64,
+    pub points: u128,
+}
+
+impl XnAccount for LegacyImport {
+    const NAME: &'static str = "LegacyImport";
+}
+
+/// Same as `sol_xen_minter::TOKENS_RECORD_VERSION`: records decoded from the older layout report 0
+pub const TOKENS_RECORD_VERSION: u8 = 1;
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct UserTokensRecord {
+    pub points_counters: [u128; 4],
+    /// solXEN minted, in token base units
+    pub tokens_minted: u128,
+    pub version: u8,
+    /// points converted so far, across all kinds
+    pub points_converted: u128,
+    /// converted points short of a whole base unit, carried over to the next mint
+    pub points_remainder: u16,
+}
+
+impl XnAccount for UserTokensRecord {
+    const NAME: &'static str = "UserTokensRecord";
+
+    // same as `sol_xen_minter::UserTokensRecord::upgrade`
+    fn upgrade(data: &[u8]) -> Option<Self> {
+        if data.len() != 80 {
+            return None;
+        }
+        let (points_counters, points_converted) = <([u128; 4], u128)>::try_from_slice(data).ok()?;
+        Some(UserTokensRecord {
+            points_counters,
+            tokens_minted: points_converted / POINTS_PER_BASE_UNIT,
+            version: 0,
+            points_converted,
+            points_remainder: 0,
+        })
+    }
+}
+
+/// Points per solXEN base unit
+pub const POINTS_PER_BASE_UNIT: u128 = 1_000;
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct SupplyRecord {
+    /// 0 means no cap
+    pub cap: u128,
+    pub tokens_minted: u128,
+    pub points_converted: u128,
+    pub points_counters: [u128; 4],
+}
+
+impl XnAccount for SupplyRecord {
+    const NAME: &'static str = "SupplyRecord";
+}
+
+/// Return data of `sol_xen_minter::get_supply_stats`
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct SupplyStats {
+    pub cap: u128,
+    pub tokens_minted: u128,
+    /// `None` when there is no cap
+    pub remaining: Option<u128>,
+    pub points_converted: u128,
+    pub points_counters: [u128; 4],
+    pub mint_supply: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct PayoutRecord {
+    pub recipient: Pubkey,
+}
+
+impl XnAccount for PayoutRecord {
+    const NAME: &'static str = "PayoutRecord";
+}
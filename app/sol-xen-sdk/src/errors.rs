@@ -0,0 +1,16 @@
+use solana_client::client_error::ClientError;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+/// `sol_xen_miner::SolXenError::MiningIsOver` (Anchor error codes start at 6000)
+pub const MINING_IS_OVER: u32 = 6009;
+
+pub fn is_custom_error(err: &TransactionError, code: u32) -> bool {
+    matches!(err, TransactionError::InstructionError(_, InstructionError::Custom(c)) if *c == code)
+}
+
+/// True if a sent (or preflight-simulated) transaction was rejected because mining is over
+pub fn is_mining_over_error(err: &ClientError) -> bool {
+    err.get_transaction_error()
+        .map(|e| is_custom_error(&e, MINING_IS_OVER))
+        .unwrap_or(false)
+}
@@ -0,0 +1,82 @@
+//! Hashes attributed to Ethereum addresses. `mine_hashes` counts them in the `xn-by-eth` record of each kind;
+//! they aren't convertible to tokens, but can be queried across all kinds and followed through `HashEventV2`.
+
+use std::collections::HashMap;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{events::HashEventV2, get_account, legacy_miner_program_ids, pda, UserEthXnRecord, MAX_KINDS};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EthHashes {
+    pub hashes: u64,
+    pub superhashes: u64,
+}
+
+/// Hashes of an Ethereum address, per kind
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EthAddressTotals {
+    pub kinds: [EthHashes; MAX_KINDS as usize],
+}
+
+impl EthAddressTotals {
+    pub fn hashes(&self) -> u64 {
+        self.kinds.iter().map(|kind| kind.hashes).sum()
+    }
+
+    pub fn superhashes(&self) -> u64 {
+        self.kinds.iter().map(|kind| kind.superhashes).sum()
+    }
+
+    fn add(&mut self, kind: u8, hashes: u64, superhashes: u64) {
+        if let Some(totals) = self.kinds.get_mut(kind as usize) {
+            totals.hashes += hashes;
+            totals.superhashes += superhashes;
+        }
+    }
+}
+
+/// Hashes of `eth_address` in the `xn-by-eth` records of all kinds: those of `miner_program`,
+/// plus those of the legacy per-kind miners (eth records aren't imported)
+pub fn fetch_eth_totals(client: &RpcClient, miner_program: &Pubkey, eth_address: &[u8; 20]) -> EthAddressTotals {
+    let legacy_miners = legacy_miner_program_ids();
+    let mut totals = EthAddressTotals::default();
+    for kind in 0..MAX_KINDS {
+        for program in [*miner_program, legacy_miners[kind as usize]] {
+            let record = get_account::<UserEthXnRecord>(client, &pda::user_eth_xn_record(eth_address, kind, &program).0);
+            if let Some(record) = record {
+                totals.add(kind, record.hashes, record.superhashes);
+            }
+        }
+    }
+    totals
+}
+
+/// Running totals by Ethereum address, from `HashEventV2` events
+#[derive(Clone, Debug, Default)]
+pub struct EthAggregator {
+    totals: HashMap<[u8; 20], EthAddressTotals>,
+}
+
+impl EthAggregator {
+    /// Starts from known totals, e.g. from `fetch_eth_totals`
+    pub fn with_totals(eth_address: [u8; 20], totals: EthAddressTotals) -> Self {
+        EthAggregator { totals: HashMap::from([(eth_address, totals)]) }
+    }
+
+    /// Adds the hashes of a `mine_hashes` tx; returns the updated totals of its Ethereum address
+    pub fn apply(&mut self, event: &HashEventV2) -> &EthAddressTotals {
+        let totals = self.totals.entry(event.eth_account).or_default();
+        totals.add(event.kind, event.hashes as u64, event.superhashes as u64);
+        totals
+    }
+
+    pub fn get(&self, eth_address: &[u8; 20]) -> Option<&EthAddressTotals> {
+        self.totals.get(eth_address)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8; 20], &EthAddressTotals)> {
+        self.totals.iter()
+    }
+}
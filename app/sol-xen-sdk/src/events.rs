@@ -0,0 +1,176 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_sdk::{hash::hash, pubkey::Pubkey};
+
+use crate::instructions::MinerParamsArgs;
+
+/// Anchor event discriminator: first 8 bytes of sha256("event:<Name>")
+pub fn event_discriminator(name: &str) -> [u8; 8] {
+    hash(format!("event:{name}").as_bytes()).to_bytes()[0..8]
+        .try_into()
+        .unwrap()
+}
+
+/// Event emitted by one of the solXEN programs with `emit!`, logged as `Program data: <base64>`
+pub trait XnEvent: BorshDeserialize {
+    const NAME: &'static str;
+
+    fn try_from_event_data(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 || data[0..8] != event_discriminator(Self::NAME) {
+            return None;
+        }
+        Self::try_from_slice(&data[8..]).ok()
+    }
+}
+
+/// Events of type `T` found in a transaction's log messages
+pub fn parse_events<T: XnEvent>(logs: &[String]) -> Vec<T> {
+    logs.iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter_map(|data| T::try_from_event_data(&data))
+        .collect()
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct HashEvent {
+    pub slot: u64,
+    pub user: Pubkey,
+    pub eth_account: [u8; 20],
+    pub hashes: u8,
+    pub superhashes: u8,
+    pub points: u64,
+}
+
+impl XnEvent for HashEvent {
+    const NAME: &'static str = "HashEvent";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct HashEventV2 {
+    pub slot: u64,
+    pub kind: u8,
+    pub user: Pubkey,
+    pub eth_account: [u8; 20],
+    pub amp: u16,
+    pub nonce: [u8; 4],
+    pub hashes: u8,
+    pub superhashes: u8,
+    pub points: u128,
+    pub user_hashes: u64,
+    pub user_superhashes: u64,
+    pub user_points: u128,
+    pub global_hashes: u64,
+    pub global_superhashes: u64,
+    pub global_points: u128,
+}
+
+impl XnEvent for HashEventV2 {
+    const NAME: &'static str = "HashEventV2";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct AmpDecayed {
+    pub kind: u8,
+    pub slot: u64,
+    pub amp: u16,
+    pub previous_amp_slot: u64,
+}
+
+impl XnEvent for AmpDecayed {
+    const NAME: &'static str = "AmpDecayed";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct TotalsCranked {
+    pub kind: u8,
+    pub slot: u64,
+    pub shards: u8,
+    pub hashes: u64,
+    pub superhashes: u64,
+    pub points: u128,
+    pub global_hashes: u64,
+    pub global_superhashes: u64,
+    pub global_points: u128,
+}
+
+impl XnEvent for TotalsCranked {
+    const NAME: &'static str = "TotalsCranked";
+}
+
+#[derive(BorshDeserialize, Clone, Debug)]
+pub struct MinerInitialized {
+    pub kind: u8,
+    pub admin: Pubkey,
+    pub slot: u64,
+    pub nonce: [u8; 4],
+    pub params: MinerParamsArgs,
+}
+
+impl XnEvent for MinerInitialized {
+    const NAME: &'static str = "MinerInitialized";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct UserRecordClosed {
+    pub kind: u8,
+    pub user: Pubkey,
+    pub hashes: u64,
+    pub superhashes: u64,
+    pub points: u128,
+    pub lamports: u64,
+}
+
+impl XnEvent for UserRecordClosed {
+    const NAME: &'static str = "UserRecordClosed";
+}
+
+/// Emitted by `sol_xen_minter::mint_tokens`; `amount` and `tokens_minted` are in token base units
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct TokensMinted {
+    pub user: Pubkey,
+    pub kind: u8,
+    pub miner_program: Pubkey,
+    pub points: u128,
+    pub points_minted: u128,
+    pub amount: u64,
+    pub points_counter: u128,
+    pub points_converted: u128,
+    pub tokens_minted: u128,
+}
+
+impl XnEvent for TokensMinted {
+    const NAME: &'static str = "TokensMinted";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct MinerRegistryUpdated {
+    pub kind: u8,
+    pub program_id: Pubkey,
+    pub allowed: bool,
+    pub frozen: bool,
+}
+
+impl XnEvent for MinerRegistryUpdated {
+    const NAME: &'static str = "MinerRegistryUpdated";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct SupplyCapUpdated {
+    pub cap: u128,
+    pub tokens_minted: u128,
+}
+
+impl XnEvent for SupplyCapUpdated {
+    const NAME: &'static str = "SupplyCapUpdated";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug)]
+pub struct PayoutSet {
+    pub user: Pubkey,
+    pub recipient: Pubkey,
+}
+
+impl XnEvent for PayoutSet {
+    const NAME: &'static str = "PayoutSet";
+}
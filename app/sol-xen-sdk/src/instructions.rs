@@ -0,0 +1,411 @@
+use borsh::{to_vec, BorshDeserialize, BorshSerialize};
+use solana_sdk::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+use crate::pda;
+
+/// Anchor instruction discriminator: first 8 bytes of sha256("global:<name>")
+pub fn instruction_discriminator(name: &str) -> [u8; 8] {
+    hash(format!("global:{name}").as_bytes()).to_bytes()[0..8]
+        .try_into()
+        .unwrap()
+}
+
+fn instruction_data<T: BorshSerialize>(name: &str, args: &T) -> Vec<u8> {
+    [
+        instruction_discriminator(name).as_slice(),
+        to_vec(args).unwrap().as_slice(),
+    ]
+    .concat()
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct EthAccount {
+    pub address: [u8; 20],
+    pub address_str: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct MinerParamsArgs {
+    pub max_hashes: u8,
+    pub hash_pattern: String,
+    pub superhash_pattern: String,
+    pub superhash_x: u16,
+    pub amp_start: u16,
+    pub amp_cycle_slots: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub mix_slot_hash: bool,
+}
+
+/// Mining params the miners were originally deployed with
+impl Default for MinerParamsArgs {
+    fn default() -> Self {
+        MinerParamsArgs {
+            max_hashes: 72,
+            hash_pattern: String::from("420"),
+            superhash_pattern: String::from("42069"),
+            superhash_x: 250,
+            amp_start: 300,
+            amp_cycle_slots: 100_000,
+            start_slot: 268484759,
+            end_slot: 0,
+            mix_slot_hash: false,
+        }
+    }
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct InitMinerArgs {
+    pub kind: u8,
+    pub params: MinerParamsArgs,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct UpdateParamsArgs {
+    pub kind: u8,
+    pub params: MinerParamsArgs,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct MineHashesArgs {
+    pub eth_address: [u8; 20],
+    pub _kind: u8,
+    pub iterations: u8,
+    pub address_str: String,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct MineHashesV2Args {
+    pub eth_address: [u8; 20],
+    pub _kind: u8,
+    pub iterations: u8,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct GetMiningStatusArgs {
+    pub kind: u8,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct ImportLegacyRecordArgs {
+    pub kind: u8,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct CrankTotalsArgs {
+    pub kind: u8,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct CloseUserRecordArgs {
+    pub kind: u8,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct MigrateTokensRecordArgs {
+    pub user: Pubkey,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct MintTokensArgs {
+    pub kind: u8,
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct UpdateMinersArgs {
+    pub kind: u8,
+    pub program_id: Pubkey,
+}
+
+/// `sol_xen_miner::init_miner`; `admin` has to be the admin stored in `MinerConfig`
+pub fn init_miner(miner_program: &Pubkey, admin: &Pubkey, kind: u8, params: MinerParamsArgs) -> Instruction {
+    let (config, _) = pda::miner_config(miner_program);
+    let (global_xn_record, _) = pda::global_xn_record(kind, miner_program);
+    let (miner_params, _) = pda::miner_params(kind, miner_program);
+    Instruction {
+        program_id: *miner_program,
+        data: instruction_data("init_miner", &InitMinerArgs { kind, params }),
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(global_xn_record, false),
+            AccountMeta::new(miner_params, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    }
+}
+
+/// `sol_xen_miner::update_params` (admin only)
+pub fn update_params(miner_program: &Pubkey, admin: &Pubkey, kind: u8, params: MinerParamsArgs) -> Instruction {
+    let (config, _) = pda::miner_config(miner_program);
+    let (miner_params, _) = pda::miner_params(kind, miner_program);
+    Instruction {
+        program_id: *miner_program,
+        data: instruction_data("update_params", &UpdateParamsArgs { kind, params }),
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(miner_params, false),
+            AccountMeta::new(pda::global_xn_record(kind, miner_program).0, false),
+        ],
+    }
+}
+
+/// `sol_xen_miner::get_mining_status`, to be simulated; decode return data as `MiningStatus`
+pub fn get_mining_status(miner_program: &Pubkey, kind: u8) -> Instruction {
+    let (global_xn_record, _) = pda::global_xn_record(kind, miner_program);
+    let (miner_params, _) = pda::miner_params(kind, miner_program);
+    Instruction {
+        program_id: *miner_program,
+        data: instruction_data("get_mining_status", &GetMiningStatusArgs { kind }),
+        accounts: vec![
+            AccountMeta::new_readonly(global_xn_record, false),
+            AccountMeta::new_readonly(miner_params, false),
+        ],
+    }
+}
+
+/// `sol_xen_miner::mine_hashes`, on the user's shard (see `crate::shard_index`), computing `iterations` hashes
+/// (up to the kind's `max_hashes`); see `crate::mine_hashes_units` for the compute unit limit to set
+pub fn mine_hashes(miner_program: &Pubkey, user: &Pubkey, eth_account: EthAccount, kind: u8, iterations: u8) -> Instruction {
+    Instruction {
+        program_id: *miner_program,
+        accounts: mine_hashes_accounts(miner_program, user, &eth_account.address, kind),
+        data: instruction_data(
+            "mine_hashes",
+            &MineHashesArgs { eth_address: eth_account.address, _kind: kind, iterations, address_str: eth_account.address_str },
+        ),
+    }
+}
+
+/// `sol_xen_miner::mine_hashes_v2`: same as `mine_hashes` without the address string, so the caller has to make sure
+/// `eth_address` is the intended one (e.g. by parsing it with `ethaddr::Address::from_str_checksum`)
+pub fn mine_hashes_v2(miner_program: &Pubkey, user: &Pubkey, eth_address: [u8; 20], kind: u8, iterations: u8) -> Instruction {
+    Instruction {
+        program_id: *miner_program,
+        accounts: mine_hashes_accounts(miner_program, user, &eth_address, kind),
+        data: instruction_data("mine_hashes_v2", &MineHashesV2Args { eth_address, _kind: kind, iterations }),
+    }
+}
+
+fn mine_hashes_accounts(miner_program: &Pubkey, user: &Pubkey, eth_address: &[u8; 20], kind: u8) -> Vec<AccountMeta> {
+    let (global_xn_record, _) = pda::global_xn_record(kind, miner_program);
+    let (global_xn_shard, _) = pda::global_xn_shard(kind, crate::shard_index(user), miner_program);
+    let (miner_params, _) = pda::miner_params(kind, miner_program);
+    let (user_eth_xn_record, _) = pda::user_eth_xn_record(eth_address, kind, miner_program);
+    let (user_sol_xn_record, _) = pda::user_sol_xn_record(user, kind, miner_program);
+    vec![
+        AccountMeta::new_readonly(global_xn_record, false),
+        AccountMeta::new(global_xn_shard, false),
+        AccountMeta::new_readonly(miner_params, false),
+        AccountMeta::new(user_eth_xn_record, false),
+        AccountMeta::new(user_sol_xn_record, false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::ID, false),
+    ]
+}
+
+/// `sol_xen_miner::crank_totals` over all shards of `kind` (those not set up yet are skipped);
+/// permissionless, needs no signer but the fee payer
+pub fn crank_totals(miner_program: &Pubkey, kind: u8) -> Instruction {
+    let (global_xn_record, _) = pda::global_xn_record(kind, miner_program);
+    let (miner_params, _) = pda::miner_params(kind, miner_program);
+    let mut accounts = vec![
+        AccountMeta::new(global_xn_record, false),
+        AccountMeta::new_readonly(miner_params, false),
+    ];
+    accounts.extend((0..crate::SHARDS).map(|shard| AccountMeta::new(pda::global_xn_shard(kind, shard, miner_program).0, false)));
+    Instruction {
+        program_id: *miner_program,
+        data: instruction_data("crank_totals", &CrankTotalsArgs { kind }),
+        accounts,
+    }
+}
+
+/// `sol_xen_miner::import_legacy_record`: adds the user's points of `kind` mined with `legacy_program` since the last
+/// import to their record
+pub fn import_legacy_record(miner_program: &Pubkey, legacy_program: &Pubkey, user: &Pubkey, kind: u8) -> Instruction {
+    let (global_xn_record, _) = pda::global_xn_record(kind, miner_program);
+    let (miner_params, _) = pda::miner_params(kind, miner_program);
+    let (legacy_record, _) = pda::user_sol_xn_record(user, kind, legacy_program);
+    let (user_sol_xn_record, _) = pda::user_sol_xn_record(user, kind, miner_program);
+    let (legacy_import, _) = pda::legacy_import(user, kind, miner_program);
+    Instruction {
+        program_id: *miner_program,
+        data: instruction_data("import_legacy_record", &ImportLegacyRecordArgs { kind }),
+        accounts: vec![
+            AccountMeta::new_readonly(global_xn_record, false),
+            AccountMeta::new_readonly(miner_params, false),
+            AccountMeta::new_readonly(legacy_record, false),
+            AccountMeta::new_readonly(*legacy_program, false),
+            AccountMeta::new(user_sol_xn_record, false),
+            AccountMeta::new(legacy_import, false),
+            AccountMeta::new(*user, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    }
+}
+
+/// `sol_xen_miner::close_user_record`: returns the rent of the user's record of `kind` once mining is over
+/// and all of its points are minted with `minter_program`
+pub fn close_user_record(miner_program: &Pubkey, minter_program: &Pubkey, user: &Pubkey, kind: u8) -> Instruction {
+    let (global_xn_record, _) = pda::global_xn_record(kind, miner_program);
+    let (miner_params, _) = pda::miner_params(kind, miner_program);
+    let (user_sol_xn_record, _) = pda::user_sol_xn_record(user, kind, miner_program);
+    let (user_tokens_record, _) = pda::user_tokens_record(user, minter_program);
+    Instruction {
+        program_id: *miner_program,
+        data: instruction_data("close_user_record", &CloseUserRecordArgs { kind }),
+        accounts: vec![
+            AccountMeta::new_readonly(global_xn_record, false),
+            AccountMeta::new_readonly(miner_params, false),
+            AccountMeta::new(user_sol_xn_record, false),
+            AccountMeta::new_readonly(user_tokens_record, false),
+            AccountMeta::new(*user, true),
+        ],
+    }
+}
+
+/// `sol_xen_minter::mint_tokens`, converting points earned with the miner of `kind`;
+/// `token_program` is the one owning the mint, see `crate::mint_token_program`, and `recipient` the user
+/// or their payout recipient, see `crate::payout_recipient`
+pub fn mint_tokens(
+    minter_program: &Pubkey,
+    miner_program: &Pubkey,
+    token_program: &Pubkey,
+    user: &Pubkey,
+    recipient: &Pubkey,
+    kind: u8,
+) -> Instruction {
+    let (user_sol_xn_record, _) = pda::user_sol_xn_record(user, kind, miner_program);
+    let (user_tokens_record, _) = pda::user_tokens_record(user, minter_program);
+    let (mint, _) = pda::mint(minter_program);
+    Instruction {
+        program_id: *minter_program,
+        data: instruction_data("mint_tokens", &MintTokensArgs { kind }),
+        accounts: vec![
+            AccountMeta::new_readonly(user_sol_xn_record, false),
+            AccountMeta::new(user_tokens_record, false),
+            AccountMeta::new(pda::user_token_account(recipient, minter_program, token_program), false),
+            AccountMeta::new(*user, true),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(*miner_program, false),
+            AccountMeta::new_readonly(pda::minter_config(minter_program).0, false),
+            AccountMeta::new(pda::supply(minter_program).0, false),
+            AccountMeta::new_readonly(*recipient, false),
+            AccountMeta::new_readonly(pda::payout(user, minter_program).0, false),
+        ],
+    }
+}
+
+/// `sol_xen_minter::mint_all`, converting points of all kinds; `user_records` are the user's `xn-by-sol` records
+/// indexed by kind, see `crate::user_records_to_mint`
+pub fn mint_all(
+    minter_program: &Pubkey,
+    token_program: &Pubkey,
+    user: &Pubkey,
+    recipient: &Pubkey,
+    user_records: &[Pubkey],
+) -> Instruction {
+    let (user_tokens_record, _) = pda::user_tokens_record(user, minter_program);
+    let (mint, _) = pda::mint(minter_program);
+    let mut accounts = vec![
+        AccountMeta::new(user_tokens_record, false),
+        AccountMeta::new(pda::user_token_account(recipient, minter_program, token_program), false),
+        AccountMeta::new(*user, true),
+        AccountMeta::new(mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        AccountMeta::new_readonly(pda::minter_config(minter_program).0, false),
+        AccountMeta::new(pda::supply(minter_program).0, false),
+        AccountMeta::new_readonly(*recipient, false),
+        AccountMeta::new_readonly(pda::payout(user, minter_program).0, false),
+    ];
+    accounts.extend(user_records.iter().map(|record| AccountMeta::new_readonly(*record, false)));
+    Instruction {
+        program_id: *minter_program,
+        data: instruction_data("mint_all", &()),
+        accounts,
+    }
+}
+
+/// `sol_xen_minter::migrate_tokens_record`: widens a legacy `UserTokensRecord` in place, `payer` covers the extra rent
+pub fn migrate_tokens_record(minter_program: &Pubkey, payer: &Pubkey, user: &Pubkey) -> Instruction {
+    let (user_tokens_record, _) = pda::user_tokens_record(user, minter_program);
+    Instruction {
+        program_id: *minter_program,
+        data: instruction_data("migrate_tokens_record", &MigrateTokensRecordArgs { user: *user }),
+        accounts: vec![
+            AccountMeta::new(user_tokens_record, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    }
+}
+
+#[derive(BorshSerialize, Clone, Debug)]
+pub struct SetPayoutArgs {
+    pub recipient: Pubkey,
+}
+
+/// `sol_xen_minter::set_payout`: the user's mints go to `recipient`'s token account from then on; can only be set once
+pub fn set_payout(minter_program: &Pubkey, user: &Pubkey, recipient: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *minter_program,
+        data: instruction_data("set_payout", &SetPayoutArgs { recipient: *recipient }),
+        accounts: vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(pda::payout(user, minter_program).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    }
+}
+
+/// `sol_xen_minter::get_supply_stats`, to be simulated; decode return data as `SupplyStats`
+pub fn get_supply_stats(minter_program: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *minter_program,
+        data: instruction_data("get_supply_stats", &()),
+        accounts: vec![
+            AccountMeta::new_readonly(pda::supply(minter_program).0, false),
+            AccountMeta::new_readonly(pda::mint(minter_program).0, false),
+        ],
+    }
+}
+
+fn update_miners(minter_program: &Pubkey, admin: &Pubkey, name: &str, kind: u8, program_id: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *minter_program,
+        data: instruction_data(name, &UpdateMinersArgs { kind, program_id: *program_id }),
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(pda::minter_config(minter_program).0, false),
+        ],
+    }
+}
+
+/// `sol_xen_minter::add_miner` (admin only): user records of `program_id` become convertible as points of `kind`
+pub fn add_miner(minter_program: &Pubkey, admin: &Pubkey, kind: u8, program_id: &Pubkey) -> Instruction {
+    update_miners(minter_program, admin, "add_miner", kind, program_id)
+}
+
+/// `sol_xen_minter::remove_miner` (admin only), unless the entry is frozen
+pub fn remove_miner(minter_program: &Pubkey, admin: &Pubkey, kind: u8, program_id: &Pubkey) -> Instruction {
+    update_miners(minter_program, admin, "remove_miner", kind, program_id)
+}
+
+/// `sol_xen_minter::freeze_miner` (admin only): the entry can't be removed afterwards
+pub fn freeze_miner(minter_program: &Pubkey, admin: &Pubkey, kind: u8, program_id: &Pubkey) -> Instruction {
+    update_miners(minter_program, admin, "freeze_miner", kind, program_id)
+}
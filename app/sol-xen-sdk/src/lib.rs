@@ -0,0 +1,266 @@
+//! Shared building blocks for solXEN Rust clients: on-chain account layouts,
+//! PDA derivation, `Instruction` builders and event decoders for `sol_xen_miner` and `sol_xen_minter`,
+//! and totals of hashes by Ethereum address (`eth`).
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_client::RpcClient,
+    rpc_config::RpcSimulateTransactionConfig,
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    message::Message,
+    pubkey,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+
+pub mod accounts;
+pub mod errors;
+pub mod eth;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+pub mod simulate;
+
+pub use accounts::*;
+pub use errors::*;
+pub use instructions::*;
+
+/// Miner program, serving all kinds
+pub const MINER: Pubkey = pubkey!("9vCHCeZcC2yqgLwbECtNDzVEi3opBWdYeGKuJ19z14th");
+
+/// Per-kind miner programs deployed before a single one served all kinds, indexed by kind
+/// (same as `sol_xen_miner::LEGACY_MINERS`)
+pub const LEGACY_MINERS: [Pubkey; MAX_KINDS as usize] = [
+    pubkey!("B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN"),
+    pubkey!("2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ"),
+    pubkey!("5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ"),
+    pubkey!("DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S"),
+];
+
+pub const MINTER: Pubkey = pubkey!("EPAdVJ5S317jJr2ejgxoA52iptvphGXjPLbqXhZH4n8o");
+
+/// Number of miner kinds (same as `sol_xen_miner::MAX_KINDS`)
+pub const MAX_KINDS: u8 = 4;
+
+/// Number of `GlobalXnShard`s per kind (same as `sol_xen_miner::SHARDS`)
+pub const SHARDS: u8 = 16;
+
+/// Shard the `mine_hashes` txs of `user` go to (same as `sol_xen_miner::shard_index`)
+pub fn shard_index(user: &Pubkey) -> u8 {
+    user.to_bytes()[0] % SHARDS
+}
+
+/// Hashes per `mine_hashes` tx the clients compute by default (the kinds' original `max_hashes`)
+pub const DEFAULT_ITERATIONS: u8 = 72;
+
+/// Compute units of `mine_hashes` besides its hash iterations: instruction and accounts handling, checks,
+/// shard and user record updates, events
+pub const MINE_HASHES_BASE_UNITS: u32 = 100_000;
+
+/// Compute units of one `mine_hashes` hash iteration (Keccak digest plus pattern matching), with some headroom.
+/// Both constants are estimates, not measured yet: `admin/bench_mine_hashes.ts` reports the figures to set them to,
+/// measured on a cluster
+pub const MINE_HASHES_UNITS_PER_ITERATION: u32 = 15_000;
+
+/// Compute unit limit a tx can request
+pub const MAX_TX_UNITS: u32 = 1_400_000;
+
+/// Most hashes a `mine_hashes` tx can compute within `MAX_TX_UNITS`, below the `max_hashes` bound
+/// of the program (`sol_xen_miner::MAX_HASHES_LIMIT`)
+pub const MAX_ITERATIONS: u8 = ((MAX_TX_UNITS - MINE_HASHES_BASE_UNITS) / MINE_HASHES_UNITS_PER_ITERATION) as u8;
+
+/// Compute unit limit for a `mine_hashes` tx computing `iterations` hashes, capped at `MAX_TX_UNITS`
+/// (the clients reject more than `MAX_ITERATIONS`); 1_180_000 for `DEFAULT_ITERATIONS`
+pub fn mine_hashes_units(iterations: u8) -> u32 {
+    (MINE_HASHES_BASE_UNITS + MINE_HASHES_UNITS_PER_ITERATION * iterations as u32).min(MAX_TX_UNITS)
+}
+
+/// Points carry 9 decimals
+pub const DECIMALS: u128 = 1_000_000_000;
+
+/// solXEN token carries 6 decimals
+pub const TOKEN_DECIMALS: u128 = 1_000_000;
+
+/// Miner program id from the `PROGRAM_ID_MINER` env var, or the built-in one
+pub fn miner_program_id() -> Pubkey {
+    std::env::var("PROGRAM_ID_MINER")
+        .map(|s| Pubkey::try_from(s.trim()).expect("Bad miner program ID"))
+        .unwrap_or(MINER)
+}
+
+/// Legacy per-kind miner program ids from the comma-delimited `MINERS` env var, or the built-in set
+pub fn legacy_miner_program_ids() -> Vec<Pubkey> {
+    match std::env::var("MINERS") {
+        Ok(miners) => {
+            let miners = miners
+                .split(',')
+                .map(|s| Pubkey::try_from(s.trim()).expect("Bad miner program ID"))
+                .collect::<Vec<Pubkey>>();
+            assert_eq!(miners.len(), MAX_KINDS as usize, "Bad miners set");
+            miners
+        }
+        Err(_) => LEGACY_MINERS.to_vec(),
+    }
+}
+
+/// Minter program id from the `PROGRAM_ID_MINTER` env var, or the built-in one
+pub fn minter_program_id() -> Pubkey {
+    std::env::var("PROGRAM_ID_MINTER")
+        .map(|s| Pubkey::try_from(s.trim()).expect("Bad minter program ID"))
+        .unwrap_or(MINTER)
+}
+
+/// Websocket endpoint of the RPC node at `rpc_url`, derived the way solana-cli does: `https` becomes `wss`,
+/// anything else `ws`, and an explicit port is bumped by one (8899 -> 8900); `None` if `rpc_url` doesn't parse
+pub fn websocket_url(rpc_url: &str) -> Option<String> {
+    let mut url = url::Url::parse(rpc_url).ok()?;
+    let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+    url.set_scheme(scheme).ok()?;
+    if let Some(port) = url.port() {
+        url.set_port(Some(port.checked_add(1)?)).ok()?;
+    }
+    Some(url.into())
+}
+
+/// AMP cycles left for the miner of `kind` at the current slot; `None` if its state can't be read
+pub fn fetch_remaining_cycles(client: &RpcClient, kind: u8, miner_program: &Pubkey) -> Option<u64> {
+    let global = get_account::<GlobalXnRecord>(client, &pda::global_xn_record(kind, miner_program).0)?;
+    let params = get_account::<MinerParams>(client, &pda::miner_params(kind, miner_program).0)?;
+    let slot = client.get_slot().ok()?;
+    Some(global.remaining_cycles(slot, &params))
+}
+
+fn is_legacy_account<T: XnAccount>(client: &RpcClient, address: &Pubkey) -> bool {
+    client
+        .get_account_data(address)
+        .is_ok_and(|data| is_legacy_record::<T>(&data))
+}
+
+/// `migrate_tokens_record` if the user's `UserTokensRecord` is still in the legacy layout; `mint_tokens` can't read it otherwise
+pub fn tokens_record_migration(client: &RpcClient, minter_program: &Pubkey, user: &Pubkey) -> Option<Instruction> {
+    is_legacy_account::<UserTokensRecord>(client, &pda::user_tokens_record(user, minter_program).0)
+        .then(|| migrate_tokens_record(minter_program, user, user))
+}
+
+/// The user's `xn-by-sol` record of each kind to pass to `mint_all`, the one of `miner_program`:
+/// legacy records are only minted once imported into it, see `legacy_import`
+pub fn user_records_to_mint(miner_program: &Pubkey, user: &Pubkey) -> Vec<Pubkey> {
+    (0..MAX_KINDS).map(|kind| pda::user_sol_xn_record(user, kind, miner_program).0).collect()
+}
+
+/// Token program owning the solXEN mint: SPL Token, or Token-2022 for a mint created with `create_mint_2022`
+pub fn mint_token_program(client: &RpcClient, minter_program: &Pubkey) -> Pubkey {
+    client
+        .get_account(&pda::mint(minter_program).0)
+        .map_or(spl_token::ID, |mint| mint.owner)
+}
+
+/// Owner of the token account the user's mints go to: the recipient set with `set_payout`, or the user
+pub fn payout_recipient(client: &RpcClient, minter_program: &Pubkey, user: &Pubkey) -> Pubkey {
+    get_account::<PayoutRecord>(client, &pda::payout(user, minter_program).0).map_or(*user, |payout| payout.recipient)
+}
+
+/// `set_payout` to `recipient`, unless the user has set a payout already (it can't be changed)
+pub fn payout_setup(client: &RpcClient, minter_program: &Pubkey, user: &Pubkey, recipient: &Pubkey) -> Option<Instruction> {
+    get_account::<PayoutRecord>(client, &pda::payout(user, minter_program).0)
+        .is_none()
+        .then(|| set_payout(minter_program, user, recipient))
+}
+
+/// Simulates `get_supply_stats` with `payer` as fee payer (nothing is signed nor sent); `None` if the minter's
+/// supply isn't tracked yet or the simulation fails
+pub fn fetch_supply_stats(client: &RpcClient, minter_program: &Pubkey, payer: &Pubkey) -> Option<SupplyStats> {
+    let message = Message::new(&[get_supply_stats(minter_program)], Some(payer));
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        ..RpcSimulateTransactionConfig::default()
+    };
+    let result = client
+        .simulate_transaction_with_config(&Transaction::new_unsigned(message), config)
+        .ok()?
+        .value;
+    let (data, _) = result.return_data.filter(|data| data.program_id == minter_program.to_string())?.data;
+    SupplyStats::try_from_slice(&STANDARD.decode(data).ok()?).ok()
+}
+
+/// Sends the `legacy_import` of every kind that has one paid by `payer`, a tx per kind (all of them along with `mint_all`
+/// wouldn't fit in one), `priority_fee` per compute unit; `mint_all` needs them first
+#[allow(clippy::result_large_err)]
+pub fn prepare_mint_all(
+    client: &RpcClient,
+    miner_program: &Pubkey,
+    payer: &Keypair,
+    priority_fee: u64,
+) -> ClientResult<Vec<Signature>> {
+    (0..MAX_KINDS)
+        .filter_map(|kind| legacy_import(client, miner_program, &payer.pubkey(), kind))
+        .map(|instruction| {
+            let transaction = Transaction::new_signed_with_payer(
+                &[ComputeBudgetInstruction::set_compute_unit_price(priority_fee), instruction],
+                Some(&payer.pubkey()),
+                &[payer],
+                client.get_latest_blockhash()?,
+            );
+            client.send_and_confirm_transaction(&transaction)
+        })
+        .collect()
+}
+
+/// `mint_all` for the user, preceded by the migration of their tokens record if it needs one;
+/// `prepare_mint_all` has to be sent first
+pub fn mint_all_instructions(
+    client: &RpcClient,
+    miner_program: &Pubkey,
+    minter_program: &Pubkey,
+    user: &Pubkey,
+) -> Vec<Instruction> {
+    let mut instructions: Vec<Instruction> = tokens_record_migration(client, minter_program, user).into_iter().collect();
+    instructions.push(mint_all(
+        minter_program,
+        &mint_token_program(client, minter_program),
+        user,
+        &payout_recipient(client, minter_program, user),
+        &user_records_to_mint(miner_program, user),
+    ));
+    instructions
+}
+
+/// `close_user_record` for the user's record of `kind`, if it can be closed:
+/// mining of `kind` is over and all of the record's points are minted
+pub fn user_record_close(
+    client: &RpcClient,
+    miner_program: &Pubkey,
+    minter_program: &Pubkey,
+    user: &Pubkey,
+    kind: u8,
+) -> Option<Instruction> {
+    let record = get_account::<UserSolXnRecord>(client, &pda::user_sol_xn_record(user, kind, miner_program).0)?;
+    if fetch_remaining_cycles(client, kind, miner_program)? > 0 {
+        return None;
+    }
+    let minted = get_account::<UserTokensRecord>(client, &pda::user_tokens_record(user, minter_program).0)
+        .map_or(0, |tokens| tokens.points_counters[kind as usize]);
+    if minted < record.points {
+        return None;
+    }
+    Some(close_user_record(miner_program, minter_program, user, kind))
+}
+
+/// `import_legacy_record` for the user's points of `kind` in its legacy miner, if it has mined some since the last import
+/// and mining of `kind` isn't over (imports aren't allowed any more then)
+pub fn legacy_import(client: &RpcClient, miner_program: &Pubkey, user: &Pubkey, kind: u8) -> Option<Instruction> {
+    let legacy_program = legacy_miner_program_ids()[kind as usize];
+    let imported = get_account::<LegacyImport>(client, &pda::legacy_import(user, kind, miner_program).0)
+        .map_or(0, |import| import.points);
+    get_account::<UserSolXnRecord>(client, &pda::user_sol_xn_record(user, kind, &legacy_program).0)
+        .filter(|record| record.points > imported)
+        .filter(|_| fetch_remaining_cycles(client, kind, miner_program).is_some_and(|cycles| cycles > 0))
+        .map(|_| import_legacy_record(miner_program, &legacy_program, user, kind))
+}
@@ -0,0 +1,98 @@
+use solana_sdk::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+pub const MINER_CONFIG_SEED: &[u8] = b"xn-miner-config";
+pub const GLOBAL_SEED: &[u8] = b"xn-miner-global";
+pub const SHARD_SEED: &[u8] = b"xn-miner-shard";
+pub const PARAMS_SEED: &[u8] = b"xn-miner-params";
+pub const USER_ETH_SEED: &[u8] = b"xn-by-eth";
+pub const USER_SOL_SEED: &[u8] = b"xn-by-sol";
+pub const USER_TOKENS_SEED: &[u8] = b"sol-xen-minted";
+pub const MINT_SEED: &[u8] = b"mint";
+pub const LEGACY_IMPORT_SEED: &[u8] = b"xn-legacy-import";
+pub const MINTER_CONFIG_SEED: &[u8] = b"xn-minter-config";
+pub const SUPPLY_SEED: &[u8] = b"xn-minter-supply";
+pub const PAYOUT_SEED: &[u8] = b"xn-payout";
+
+pub fn miner_config(miner_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINER_CONFIG_SEED], miner_program)
+}
+
+pub fn global_xn_record(kind: u8, miner_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[GLOBAL_SEED, kind.to_be_bytes().as_slice()],
+        miner_program,
+    )
+}
+
+/// Shard `shard` of `kind`; the one a user mines with is `crate::shard_index(user)`
+pub fn global_xn_shard(kind: u8, shard: u8, miner_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SHARD_SEED, kind.to_be_bytes().as_slice(), shard.to_be_bytes().as_slice()],
+        miner_program,
+    )
+}
+
+pub fn miner_params(kind: u8, miner_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PARAMS_SEED, kind.to_be_bytes().as_slice()],
+        miner_program,
+    )
+}
+
+pub fn user_eth_xn_record(eth_address: &[u8; 20], kind: u8, miner_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            USER_ETH_SEED,
+            eth_address.as_slice(),
+            kind.to_be_bytes().as_slice(),
+            miner_program.as_ref(),
+        ],
+        miner_program,
+    )
+}
+
+pub fn user_sol_xn_record(user: &Pubkey, kind: u8, miner_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            USER_SOL_SEED,
+            user.as_ref(),
+            kind.to_be_bytes().as_slice(),
+            miner_program.as_ref(),
+        ],
+        miner_program,
+    )
+}
+
+pub fn legacy_import(user: &Pubkey, kind: u8, miner_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LEGACY_IMPORT_SEED, user.as_ref(), kind.to_be_bytes().as_slice()],
+        miner_program,
+    )
+}
+
+pub fn user_tokens_record(user: &Pubkey, minter_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[USER_TOKENS_SEED, user.as_ref()], minter_program)
+}
+
+pub fn minter_config(minter_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINTER_CONFIG_SEED], minter_program)
+}
+
+pub fn supply(minter_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SUPPLY_SEED], minter_program)
+}
+
+pub fn payout(user: &Pubkey, minter_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PAYOUT_SEED, user.as_ref()], minter_program)
+}
+
+pub fn mint(minter_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINT_SEED], minter_program)
+}
+
+/// User's solXEN associated token account (or their payout recipient's, see `crate::payout_recipient`);
+/// `token_program` is the one owning the mint, see `crate::mint_token_program`
+pub fn user_token_account(user: &Pubkey, minter_program: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(user, &mint(minter_program).0, token_program)
+}
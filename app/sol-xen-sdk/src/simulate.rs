@@ -0,0 +1,135 @@
+//! Off-chain replica of `sol_xen_miner::find_hashes` and the points formula of `mine_hashes`.
+//!
+//! The outcome of a `mine_hashes` tx depends only on the slot it lands in and on the nonce of the user's shard,
+//! which is rotated by every successful `mine_hashes` on that shard. So predictions hold for the
+//! next tx to land there, and have to be recomputed once the nonce changes.
+//!
+//! Kinds with `mix_slot_hash` set also mix in the parent slot hash, which isn't known ahead of time:
+//! nothing can be predicted for them.
+
+use sha3::{Digest, Keccak256};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{get_account, pda, shard_index, GlobalXnRecord, GlobalXnShard, MinerParams, DECIMALS};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlotPrediction {
+    pub slot: u64,
+    pub amp: u16,
+    pub hashes: u8,
+    pub superhashes: u8,
+    pub points: u128,
+}
+
+/// Same as `sol_xen_miner::find_hashes`: (hashes, superhashes) found by a tx of `iterations` landing in `slot`,
+/// given the parent slot hash if the kind mixes it in
+pub fn find_hashes(slot: u64, nonce: [u8; 4], slot_hash: Option<[u8; 32]>, iterations: u8, params: &MinerParams) -> (u8, u8) {
+    let mut hashes = 0;
+    let mut superhashes = 0;
+    for i in 0..iterations {
+        let mut hasher = Keccak256::new();
+        hasher.update(nonce.as_slice());
+        hasher.update(slot.to_le_bytes());
+        if let Some(slot_hash) = slot_hash {
+            hasher.update(slot_hash);
+        }
+        hasher.update(i.to_le_bytes());
+        let result = hasher.finalize();
+        if contains_hex_pattern(&result, params.superhash_pattern.as_bytes()) {
+            superhashes += 1;
+        } else if contains_hex_pattern(&result, params.hash_pattern.as_bytes()) {
+            hashes += 1;
+        }
+    }
+    (hashes, superhashes)
+}
+
+/// Same as `sol_xen_miner::contains_hex_pattern`
+pub fn contains_hex_pattern(hash: &[u8], pattern: &[u8]) -> bool {
+    let nibbles = hash.len() * 2;
+    if pattern.len() > nibbles {
+        return false;
+    }
+    (0..=nibbles - pattern.len()).any(|start| {
+        pattern.iter().enumerate().all(|(j, &c)| {
+            let byte = hash[(start + j) / 2];
+            let nibble = if (start + j) & 1 == 0 { byte >> 4 } else { byte & 0x0f };
+            match c {
+                b'0'..=b'9' => c - b'0' == nibble,
+                b'a'..=b'f' => c - b'a' + 10 == nibble,
+                _ => false,
+            }
+        })
+    })
+}
+
+/// AMP `mine_hashes` would use in `slot`, including the decay not stored by `crank_totals` yet
+pub fn amp_at(global: &GlobalXnRecord, params: &MinerParams, slot: u64) -> u16 {
+    global.amp_at(slot, params)
+}
+
+/// Same as `sol_xen_miner::GlobalXnRecord::initial_shard_nonce`: nonce `mine_hashes` seeds a new shard with
+pub fn initial_shard_nonce(global: &GlobalXnRecord, shard: u8) -> [u8; 4] {
+    let mut hasher = Keccak256::new();
+    hasher.update(global.nonce);
+    hasher.update(shard.to_le_bytes());
+    hasher.finalize()[0..4].try_into().unwrap()
+}
+
+/// Points credited by `mine_hashes` (with 9 decimals, see `DECIMALS`)
+pub fn points(amp: u16, hashes: u8, superhashes: u8, superhash_x: u16) -> u128 {
+    DECIMALS * amp as u128 * hashes as u128
+        + DECIMALS * amp as u128 * superhash_x as u128 * superhashes as u128
+}
+
+/// Expected outcome of the next `mine_hashes` tx of `iterations` on a shard with `nonce` for each of `count` slots
+/// starting at `from_slot`. Slots where the tx would be rejected (not started yet, or mining is over) are skipped,
+/// and there are none at all if the kind mixes in slot hashes or `iterations` are out of bounds
+pub fn predict(
+    global: &GlobalXnRecord,
+    nonce: [u8; 4],
+    params: &MinerParams,
+    iterations: u8,
+    from_slot: u64,
+    count: u64,
+) -> Vec<SlotPrediction> {
+    if params.mix_slot_hash || iterations == 0 || iterations > params.max_hashes {
+        return vec![];
+    }
+    (from_slot..from_slot.saturating_add(count))
+        .filter(|&slot| slot > params.start_slot && (params.end_slot == 0 || slot < params.end_slot))
+        .filter_map(|slot| {
+            let amp = amp_at(global, params, slot);
+            if amp == 0 {
+                return None;
+            }
+            let (hashes, superhashes) = find_hashes(slot, nonce, None, iterations, params);
+            Some(SlotPrediction {
+                slot,
+                amp,
+                hashes,
+                superhashes,
+                points: points(amp, hashes, superhashes, params.superhash_x),
+            })
+        })
+        .collect()
+}
+
+/// Reads the miner state of `kind` and the user's shard, and predicts `count` slots ahead of the current one
+pub fn fetch_predictions(
+    client: &RpcClient,
+    kind: u8,
+    miner_program: &Pubkey,
+    user: &Pubkey,
+    iterations: u8,
+    count: u64,
+) -> Option<Vec<SlotPrediction>> {
+    let global = get_account::<GlobalXnRecord>(client, &pda::global_xn_record(kind, miner_program).0)?;
+    let params = get_account::<MinerParams>(client, &pda::miner_params(kind, miner_program).0)?;
+    let shard = shard_index(user);
+    let nonce = get_account::<GlobalXnShard>(client, &pda::global_xn_shard(kind, shard, miner_program).0)
+        .map_or_else(|| initial_shard_nonce(&global, shard), |shard| shard.nonce);
+    let slot = client.get_slot().ok()?;
+    Some(predict(&global, nonce, &params, iterations, slot + 1, count))
+}
@@ -0,0 +1,83 @@
+use borsh::to_vec;
+use proptest::prelude::*;
+use solana_sdk::pubkey::Pubkey;
+use sol_xen_sdk::{
+    account_discriminator, is_legacy_record, GlobalXnRecord, MinterConfig, UserEthXnRecord, UserSolXnRecord,
+    UserTokensRecord, XnAccount, MINER, RECORD_VERSION, TOKENS_RECORD_VERSION,
+};
+
+fn account_data<T: XnAccount>(fields: impl borsh::BorshSerialize) -> Vec<u8> {
+    [account_discriminator(T::NAME).as_slice(), &to_vec(&fields).unwrap()].concat()
+}
+
+proptest! {
+    #[test]
+    fn decodes_all_user_record_versions(hashes in any::<u64>(), superhashes in any::<u32>(), points in any::<u128>()) {
+        let versions = [
+            (0, account_data::<UserSolXnRecord>((hashes, superhashes, points))),
+            (1, account_data::<UserSolXnRecord>((hashes, superhashes as u64, points))),
+            (RECORD_VERSION, account_data::<UserSolXnRecord>((RECORD_VERSION, hashes, superhashes as u64, points))),
+        ];
+        for (version, data) in versions {
+            prop_assert_eq!(is_legacy_record::<UserSolXnRecord>(&data), version != RECORD_VERSION);
+            let record = UserSolXnRecord::try_from_account_data(&data).unwrap();
+            prop_assert_eq!(
+                (record.version, record.hashes, record.superhashes, record.points),
+                (version, hashes, superhashes as u64, points)
+            );
+        }
+
+        let versions = [
+            (0, account_data::<UserEthXnRecord>((hashes, superhashes))),
+            (1, account_data::<UserEthXnRecord>((hashes, superhashes as u64))),
+            (RECORD_VERSION, account_data::<UserEthXnRecord>((RECORD_VERSION, hashes, superhashes as u64))),
+        ];
+        for (version, data) in versions {
+            let record = UserEthXnRecord::try_from_account_data(&data).unwrap();
+            prop_assert_eq!((record.version, record.hashes, record.superhashes), (version, hashes, superhashes as u64));
+            prop_assert!(UserSolXnRecord::try_from_account_data(&data).is_err());
+        }
+    }
+
+    #[test]
+    fn decodes_all_global_record_versions(amp in any::<u16>(), hashes in any::<u64>(), superhashes in any::<u32>(), points in any::<u128>()) {
+        let head = (amp, 42u64, [1u8, 2, 3, 4], 3u8);
+        let versions = [
+            (0, account_data::<GlobalXnRecord>((head, hashes, superhashes, points))),
+            (1, account_data::<GlobalXnRecord>((head, hashes, superhashes as u64, points))),
+            (RECORD_VERSION, account_data::<GlobalXnRecord>((RECORD_VERSION, head, hashes, superhashes as u64, points))),
+        ];
+        for (version, data) in versions {
+            prop_assert_eq!(is_legacy_record::<GlobalXnRecord>(&data), version != RECORD_VERSION);
+            let record = GlobalXnRecord::try_from_account_data(&data).unwrap();
+            prop_assert_eq!(
+                (record.version, record.amp, record.last_amp_slot, record.nonce, record.kind, record.hashes, record.superhashes, record.points),
+                (version, amp, 42, [1, 2, 3, 4], 3, hashes, superhashes as u64, points)
+            );
+        }
+    }
+}
+
+#[test]
+fn decodes_legacy_tokens_record() {
+    let data = account_data::<UserTokensRecord>(([1_000u128, 0, 2_500, 7], 3_507u128));
+    assert!(is_legacy_record::<UserTokensRecord>(&data));
+    let record = UserTokensRecord::try_from_account_data(&data).unwrap();
+    assert_eq!(record.version, 0);
+    assert_eq!((record.points_converted, record.tokens_minted, record.points_remainder), (3_507, 3, 0));
+
+    let data = account_data::<UserTokensRecord>(([1_000u128, 0, 2_500, 7], 3u128, TOKENS_RECORD_VERSION, 3_507u128, 507u16));
+    assert!(!is_legacy_record::<UserTokensRecord>(&data));
+    assert_eq!(UserTokensRecord::try_from_account_data(&data).unwrap().version, TOKENS_RECORD_VERSION);
+}
+
+#[test]
+fn decodes_minter_config() {
+    let admin = Pubkey::new_unique();
+    let data = account_data::<MinterConfig>((admin, Pubkey::default(), vec![(0u8, MINER, false), (2u8, MINER, true)]));
+    let config = MinterConfig::try_from_account_data(&data).unwrap();
+    assert_eq!(config.admin, admin);
+    assert!(config.is_allowed(0, &MINER) && config.is_allowed(2, &MINER));
+    assert!(!config.is_allowed(1, &MINER));
+    assert!(config.miners[1].frozen);
+}
@@ -0,0 +1,49 @@
+use sol_xen_sdk::{
+    eth::{EthAddressTotals, EthAggregator, EthHashes},
+    events::HashEventV2,
+};
+use solana_sdk::pubkey::Pubkey;
+
+fn hash_event(eth_account: [u8; 20], kind: u8, hashes: u8, superhashes: u8) -> HashEventV2 {
+    HashEventV2 {
+        slot: 1,
+        kind,
+        user: Pubkey::new_unique(),
+        eth_account,
+        amp: 300,
+        nonce: [0; 4],
+        hashes,
+        superhashes,
+        points: 0,
+        user_hashes: 0,
+        user_superhashes: 0,
+        user_points: 0,
+        global_hashes: 0,
+        global_superhashes: 0,
+        global_points: 0,
+    }
+}
+
+#[test]
+fn aggregates_hashes_by_eth_address_across_kinds() {
+    let (alice, bob) = ([1u8; 20], [2u8; 20]);
+    let mut known = EthAddressTotals::default();
+    known.kinds[3] = EthHashes { hashes: 100, superhashes: 1 };
+    let mut aggregator = EthAggregator::with_totals(alice, known);
+
+    aggregator.apply(&hash_event(alice, 0, 3, 0));
+    aggregator.apply(&hash_event(bob, 0, 5, 1));
+    let totals = aggregator.apply(&hash_event(alice, 2, 2, 1)).clone();
+
+    assert_eq!(totals.kinds[0], EthHashes { hashes: 3, superhashes: 0 });
+    assert_eq!(totals.kinds[2], EthHashes { hashes: 2, superhashes: 1 });
+    assert_eq!((totals.hashes(), totals.superhashes()), (105, 2));
+    assert_eq!(aggregator.get(&bob).unwrap().hashes(), 5);
+    assert_eq!(aggregator.iter().count(), 2);
+}
+
+#[test]
+fn ignores_unknown_kinds() {
+    let mut aggregator = EthAggregator::default();
+    assert_eq!(aggregator.apply(&hash_event([1; 20], 7, 3, 0)).hashes(), 0);
+}
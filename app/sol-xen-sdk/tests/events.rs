@@ -0,0 +1,39 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::to_vec;
+use sol_xen_sdk::events::{event_discriminator, parse_events, AmpDecayed, HashEvent, HashEventV2, XnEvent};
+use solana_sdk::pubkey::Pubkey;
+
+fn log_line<T: XnEvent>(fields: impl borsh::BorshSerialize) -> String {
+    let data = [event_discriminator(T::NAME).as_slice(), &to_vec(&fields).unwrap()].concat();
+    format!("Program data: {}", STANDARD.encode(data))
+}
+
+#[test]
+fn parses_events_from_mine_hashes_logs() {
+    let user = Pubkey::new_unique();
+    let logs = vec![
+        "Program 9vCHCeZcC2yqgLwbECtNDzVEi3opBWdYeGKuJ19z14th invoke [1]".to_string(),
+        log_line::<AmpDecayed>((2u8, 500u64, 299u16, 100u64)),
+        log_line::<HashEventV2>((
+            500u64, 2u8, user, [7u8; 20], 299u16, [1u8, 2, 3, 4], 3u8, 1u8,
+            (1u128 << 70, 10u64, 2u64, 1u128 << 71),
+            (100u64, 20u64, 1u128 << 72),
+        )),
+        log_line::<HashEvent>((500u64, user, [7u8; 20], 3u8, 1u8, 42u64)),
+        "Program 9vCHCeZcC2yqgLwbECtNDzVEi3opBWdYeGKuJ19z14th success".to_string(),
+    ];
+
+    let decayed = parse_events::<AmpDecayed>(&logs);
+    assert_eq!(decayed.len(), 1);
+    assert_eq!((decayed[0].kind, decayed[0].amp, decayed[0].previous_amp_slot), (2, 299, 100));
+
+    let hashes = parse_events::<HashEventV2>(&logs);
+    assert_eq!(hashes.len(), 1);
+    let event = &hashes[0];
+    assert_eq!((event.kind, event.user, event.amp, event.nonce), (2, user, 299, [1, 2, 3, 4]));
+    assert_eq!((event.hashes, event.superhashes, event.points), (3, 1, 1 << 70));
+    assert_eq!((event.user_hashes, event.user_superhashes, event.user_points), (10, 2, 1 << 71));
+    assert_eq!((event.global_hashes, event.global_superhashes, event.global_points), (100, 20, 1 << 72));
+
+    assert_eq!(parse_events::<HashEvent>(&logs)[0].points, 42);
+}
@@ -0,0 +1,13 @@
+use sol_xen_sdk::websocket_url;
+
+#[test]
+fn websocket_url_follows_solana_cli() {
+    assert_eq!(websocket_url("http://localhost:8899").unwrap(), "ws://localhost:8900/");
+    assert_eq!(websocket_url("https://api.devnet.solana.com").unwrap(), "wss://api.devnet.solana.com/");
+    assert_eq!(websocket_url("https://rpc.example.com:443/").unwrap(), "wss://rpc.example.com/");
+    assert_eq!(
+        websocket_url("https://rpc.example.com:8443/http-8899?key=8899").unwrap(),
+        "wss://rpc.example.com:8444/http-8899?key=8899"
+    );
+    assert!(websocket_url("not a url").is_none());
+}
@@ -0,0 +1,110 @@
+#[path = "../../../programs/sol-xen-miner/tests/common/mod.rs"]
+mod common;
+
+use proptest::prelude::*;
+use sol_xen_sdk::{shard_index, simulate, GlobalXnRecord, MinerParams};
+use solana_sdk::pubkey::Pubkey;
+
+// SDK copies of the program fixtures
+fn sdk_params(params: &sol_xen_miner::MinerParams) -> MinerParams {
+    MinerParams {
+        version: params.version,
+        kind: params.kind,
+        max_hashes: params.max_hashes,
+        hash_pattern: params.hash_pattern.clone(),
+        superhash_pattern: params.superhash_pattern.clone(),
+        superhash_x: params.superhash_x,
+        amp_start: params.amp_start,
+        amp_cycle_slots: params.amp_cycle_slots,
+        start_slot: params.start_slot,
+        end_slot: params.end_slot,
+        mix_slot_hash: params.mix_slot_hash,
+    }
+}
+
+fn sdk_global(global: &sol_xen_miner::GlobalXnRecord) -> GlobalXnRecord {
+    GlobalXnRecord {
+        version: global.version,
+        amp: global.amp,
+        last_amp_slot: global.last_amp_slot,
+        nonce: global.nonce,
+        kind: global.kind,
+        hashes: global.hashes,
+        superhashes: global.superhashes,
+        points: global.points,
+    }
+}
+
+fn global(amp: u16, last_amp_slot: u64) -> GlobalXnRecord {
+    sdk_global(&common::global(amp, last_amp_slot))
+}
+
+#[test]
+fn predict_skips_closed_slots_and_decays_amp() {
+    let mut params = sdk_params(&common::default_params());
+    params.start_slot = 100;
+    params.end_slot = 150;
+    let predictions = simulate::predict(&global(300, 0), [1, 2, 3, 4], &params, 72, 90, 100);
+    assert_eq!(predictions.first().unwrap().slot, 101);
+    assert_eq!(predictions.last().unwrap().slot, 149);
+
+    params.end_slot = 0;
+    let predictions = simulate::predict(&global(300, 0), [1, 2, 3, 4], &params, 72, 100_000, 2);
+    assert_eq!(predictions[0].amp, 300);
+    assert_eq!(predictions[1].amp, 299);
+
+    assert!(simulate::predict(&global(1, 0), [1, 2, 3, 4], &params, 72, 100_001, 10).is_empty());
+
+    assert!(simulate::predict(&global(300, 0), [1, 2, 3, 4], &params, 73, 100, 10).is_empty());
+    let fewer = simulate::predict(&global(300, 0), [1, 2, 3, 4], &params, 36, 100, 10);
+    assert!(fewer.iter().zip(&predictions).all(|(fewer, all)| fewer.hashes <= all.hashes && fewer.points <= all.points));
+
+    params.mix_slot_hash = true;
+    assert!(simulate::predict(&global(300, 0), [1, 2, 3, 4], &params, 72, 100, 10).is_empty());
+}
+
+#[test]
+fn points_match_mine_hashes() {
+    assert_eq!(simulate::points(300, 2, 1, 250), 1_000_000_000 * 300 * 2 + 1_000_000_000 * 300 * 250);
+}
+
+proptest! {
+    #[test]
+    fn find_hashes_matches_program(
+        slot in any::<u64>(),
+        nonce in any::<[u8; 4]>(),
+        hash_pattern in "[0-9a-f]{1,3}",
+        superhash_suffix in "[0-9a-f]{1,2}",
+        slot_hash in any::<Option<[u8; 32]>>(),
+        iterations in 1u8..=128,
+    ) {
+        let program_params = common::params(&hash_pattern, &format!("{hash_pattern}{superhash_suffix}"), 128);
+        prop_assert_eq!(
+            simulate::find_hashes(slot, nonce, slot_hash, iterations, &sdk_params(&program_params)),
+            sol_xen_miner::find_hashes(slot, nonce, slot_hash, iterations, &program_params)
+        );
+    }
+
+    #[test]
+    fn shard_state_matches_program(
+        amp in any::<u16>(),
+        last_amp_slot in any::<u64>(),
+        slot in any::<u64>(),
+        amp_cycle_slots in any::<u64>(),
+        nonce in any::<[u8; 4]>(),
+        user in any::<[u8; 32]>(),
+    ) {
+        let program_params = sol_xen_miner::MinerParams { amp_cycle_slots, ..common::default_params() };
+        let program_global = sol_xen_miner::GlobalXnRecord { nonce, ..common::global(amp, last_amp_slot) };
+        let (params, global) = (sdk_params(&program_params), sdk_global(&program_global));
+
+        prop_assert_eq!(simulate::amp_at(&global, &params, slot), program_global.amp_at(slot, &program_params));
+        prop_assert_eq!(
+            global.remaining_cycles(slot, &params),
+            program_global.remaining_cycles(slot, &program_params)
+        );
+        let shard = shard_index(&Pubkey::new_from_array(user));
+        prop_assert_eq!(shard, sol_xen_miner::shard_index(&Pubkey::new_from_array(user)));
+        prop_assert_eq!(simulate::initial_shard_nonce(&global, shard), program_global.initial_shard_nonce(shard));
+    }
+}
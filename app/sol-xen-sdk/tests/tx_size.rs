@@ -0,0 +1,40 @@
+use sol_xen_sdk::{
+    import_legacy_record, migrate_tokens_record, mint_all, user_records_to_mint, LEGACY_MINERS,
+    MAX_KINDS, MINER, MINTER,
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, message::Message, packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey, transaction::Transaction,
+};
+
+fn tx_size(instructions: &[Instruction], payer: &Pubkey) -> usize {
+    let transaction = Transaction::new_unsigned(Message::new(instructions, Some(payer)));
+    bincode::serialized_size(&transaction).unwrap() as usize
+}
+
+#[test]
+fn mint_all_txs_fit_in_a_packet() {
+    let user = Pubkey::new_unique();
+    let price = ComputeBudgetInstruction::set_compute_unit_price(1_000_000);
+
+    // the legacy import of a kind
+    for kind in 0..MAX_KINDS {
+        let import = [price.clone(), import_legacy_record(&MINER, &LEGACY_MINERS[kind as usize], &user, kind)];
+        assert!(tx_size(&import, &user) <= PACKET_DATA_SIZE);
+    }
+
+    // tokens record migration and mint_all, paid out to another wallet
+    let mint = [
+        price.clone(),
+        migrate_tokens_record(&MINTER, &user, &user),
+        mint_all(&MINTER, &spl_token::ID, &user, &Pubkey::new_unique(), &user_records_to_mint(&MINER, &user)),
+    ];
+    assert!(tx_size(&mint, &user) <= PACKET_DATA_SIZE);
+
+    // all of it in one tx wouldn't fit
+    let all: Vec<Instruction> = (0..MAX_KINDS)
+        .map(|kind| import_legacy_record(&MINER, &LEGACY_MINERS[kind as usize], &user, kind))
+        .chain(mint)
+        .collect();
+    assert!(tx_size(&all, &user) > PACKET_DATA_SIZE);
+}
@@ -0,0 +1,13 @@
+use sol_xen_sdk::{
+    mine_hashes_units, DEFAULT_ITERATIONS, MAX_ITERATIONS, MAX_TX_UNITS, MINE_HASHES_BASE_UNITS,
+    MINE_HASHES_UNITS_PER_ITERATION,
+};
+
+#[test]
+fn units_stay_within_the_tx_limit() {
+    assert_eq!(mine_hashes_units(DEFAULT_ITERATIONS), 1_180_000);
+    assert!(mine_hashes_units(MAX_ITERATIONS) <= MAX_TX_UNITS);
+    // one more iteration wouldn't fit
+    assert!(MINE_HASHES_BASE_UNITS + MINE_HASHES_UNITS_PER_ITERATION * (MAX_ITERATIONS as u32 + 1) > MAX_TX_UNITS);
+    assert_eq!(mine_hashes_units(sol_xen_miner::MAX_HASHES_LIMIT), MAX_TX_UNITS);
+}
@@ -1,23 +1,15 @@
 use std::collections::HashMap;
-use std::mem::size_of;
 use std::ops::Sub;
 use solana_client::rpc_client::RpcClient;
 use solana_client::pubsub_client::{PubsubClient};
-use solana_sdk::hash::{hash};
 use solana_sdk::{
-    system_program,
-    pubkey::Pubkey,
     signature::{Signer, read_keypair_file},
     transaction::Transaction,
-    instruction::{Instruction, AccountMeta},
     compute_budget::ComputeBudgetInstruction,
 };
-use spl_token;
-use spl_associated_token_account::get_associated_token_address_with_program_id;
 use clap::{Parser};
 use std::{process};
 use std::sync::{Arc, mpsc};
-use borsh::{BorshSerialize, BorshDeserialize, to_vec, BorshSchema};
 use ethaddr::Address;
 use colored::*;
 use dotenv::dotenv;
@@ -43,14 +35,16 @@ use {
 use jsonrpsee::core::client::{ClientT};
 use jsonrpsee::rpc_params;
 use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
-
-const MINERS: &str = "B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN,2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ,5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ,DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S";
-
-const MINTER: &str = "5tungisGgk1fmGXxacNT1NkPtZgRwJnJPVMK5kfHqx2m";
-
-const MAX_MINERS: u8 = 4;
-
-const DECIMALS: u128 = 1_000_000_000;
+use sol_xen_sdk::{
+    pda,
+    get_account,
+    UserEthXnRecord,
+    UserSolXnRecord,
+    UserTokensRecord,
+    DECIMALS,
+    TOKEN_DECIMALS,
+    MAX_KINDS,
+};
 
 /*
     Color::Red => "31".into(),
@@ -64,18 +58,6 @@ const Y: &str = "\x1b[0;33m";
 // const B: &str = "\x1b[0;34m";
 const U: &str = "\x1b[0;39m";
 
-#[derive(BorshSerialize, Debug)]
-pub struct EthAccount {
-    pub address: [u8; 20],
-    pub address_str: String,
-}
-
-#[derive(BorshSerialize, Debug)]
-pub struct MineHashes {
-    pub eth_account: EthAccount,
-    pub _kind: u8,
-}
-
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
@@ -87,8 +69,17 @@ struct Args {
     kind: u8,
     #[arg(short, long, default_value_t = 1)]
     fee: u64,
-    #[arg(short, long, default_value_t = 1_180_000)]
-    units: u32,
+    /// Compute unit limit per mine tx, derived from `iterations` if not set
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=sol_xen_sdk::MAX_TX_UNITS as i64))]
+    units: Option<u32>,
+    /// Hashes computed per mine tx, up to the kind's max_hashes and `sol_xen_sdk::MAX_ITERATIONS`
+    #[arg(
+        short,
+        long,
+        default_value_t = sol_xen_sdk::DEFAULT_ITERATIONS,
+        value_parser = clap::value_parser!(u8).range(1..=sol_xen_sdk::MAX_ITERATIONS as i64)
+    )]
+    iterations: u8,
     #[arg(short, long, default_value_t = 1)]
     runs: u32,
     #[arg(short, long, default_value_t = 0.5)]
@@ -101,45 +92,6 @@ struct Args {
     wallet_path: Option<String>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone)]
-pub struct GlobalXnRecord {
-    pub amp: u16,
-    pub last_amp_slot: u64,
-    pub nonce: [u8; 4],
-    pub kind: u8,
-    pub hashes: u64,
-    pub superhashes: u32,
-    pub points: u128,
-} // 38 <> 48
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone)]
-pub struct BoxedGlobalXnRecord {
-    pub data: Box<GlobalXnRecord>,
-}
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
-pub struct UserEthXnRecord {
-    pub hashes: u64,
-    // 8
-    pub superhashes: u32, // 4
-} // 16 == 16
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone)]
-pub struct UserSolXnRecord {
-    pub hashes: u64,
-    // 8
-    pub superhashes: u32,
-    // 4
-    pub points: u128, // 16
-} // 28
-
-#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone)]
-pub struct UserTokensRecord {
-    pub points_counters: [u128; 4],
-    // 4 * 16 = 64
-    pub tokens_minted: u128, // 16
-} // 80
-
 pub struct MineParams {
     ethereum_address: String,
     address: [u8; 20],
@@ -148,12 +100,13 @@ pub struct MineParams {
     kind: u8,
     delay: f32,
     units: u32,
+    iterations: u8,
     jito_tip: Option<u64>,
     tippers: Vec<String>,
 }
 
-impl From<MineParams> for (String, [u8; 20], u64, u32, u8, f32, u32, Option<u64>, Vec<String>) {
-    fn from(x: MineParams) -> (String, [u8; 20], u64, u32, u8, f32, u32, Option<u64>, Vec<String>) {
+impl From<MineParams> for (String, [u8; 20], u64, u32, u8, f32, u32, u8, Option<u64>, Vec<String>) {
+    fn from(x: MineParams) -> (String, [u8; 20], u64, u32, u8, f32, u32, u8, Option<u64>, Vec<String>) {
         let MineParams {
             ethereum_address,
             address,
@@ -162,10 +115,11 @@ impl From<MineParams> for (String, [u8; 20], u64, u32, u8, f32, u32, Option<u64>
             kind,
             delay,
             units,
+            iterations,
             jito_tip,
             tippers,
         } = x;
-        (ethereum_address, address, priority_fee, runs, kind, delay, units, jito_tip, tippers)
+        (ethereum_address, address, priority_fee, runs, kind, delay, units, iterations, jito_tip, tippers)
     }
 }
 
@@ -193,7 +147,8 @@ async fn main() {
     let runs = args.runs;
     // let kind = args.kind;
     let delay = args.delay;
-    let units = args.units;
+    let iterations = args.iterations;
+    let units = args.units.unwrap_or(sol_xen_sdk::mine_hashes_units(iterations));
     let keypair_path = args.wallet_path
         .or(std::env::var("USER_WALLET_PATH").ok())
         .expect("Either set USER_WALLET_PATH env var, or pass it as -w command line param");
@@ -215,7 +170,7 @@ async fn main() {
     let ws_url_ = str::replace(url.as_str(), "http", "ws");
     let ws_url = str::replace(ws_url_.as_str(), "8899", "8900");
     let jito_url = if jito_tip.is_some() {
-        std::env::var("JITO_PROVIDER_URL").map(|u| Some(u))
+        std::env::var("JITO_PROVIDER_URL").map(Some)
             .expect("JITO_PROVIDER_URL must be set with 'jito_tip' param.")
     } else { None };
 
@@ -237,65 +192,63 @@ async fn main() {
         .build()
         .unwrap();
 
-    for kind in 0..MAX_MINERS {
+    for kind in 0..MAX_KINDS {
         let keypair_path_norm = if keypair_path.ends_with("/")
         { keypair_path.clone() } else { keypair_path.clone() + "/" };
         let keypair_fn = format!("{keypair_path_norm}id{kind}.json");
 
-        let _ = match read_keypair_file(&keypair_fn) {
-            Ok(keypair) => {
-                wallets.insert(kind, keypair.insecure_clone());
-                let a = ethereum_address.clone();
-                let txc = tx_clone.clone();
-                let keypair_clone = keypair.insecure_clone();
-                rt.spawn(
-                    do_mine(
-                        keypair,
-                        MineParams {
-                            ethereum_address: a,
-                            address: *_address,
-                            priority_fee,
-                            runs,
-                            kind,
-                            delay,
-                            units,
-                            jito_tip,
-                            tippers: tippers.clone(),
-                        },
-                        txc)
-                );
-                // h.join().unwrap();
-                if automint > 0 {
-                    let tx_clone1 = tx.clone();
-                    let ws = ws_url.clone();
-                    let _hm = thread::spawn(move || {
-                        let mut last_slot: Slot = 0;
-                        let _ = match PubsubClient::slot_subscribe(ws.as_str()) {
-                            Ok(subs) => {
-                                for slot in subs.1 {
-                                    let txcm = tx_clone1.clone();
-                                    let kpm = keypair_clone.insecure_clone();
-                                    if slot.slot.sub(last_slot).ge(&(automint as u64)) {
-                                        last_slot = slot.slot;
-                                        do_mint(
-                                            kpm,
-                                            MintParams {
-                                                slot: slot.slot,
-                                                priority_fee,
-                                                kind,
-                                                automint,
-                                            },
-                                            txcm,
-                                        )
-                                    }
+        if let Ok(keypair) = read_keypair_file(&keypair_fn) {
+            wallets.insert(kind, keypair.insecure_clone());
+            let a = ethereum_address.clone();
+            let txc = tx_clone.clone();
+            let keypair_clone = keypair.insecure_clone();
+            rt.spawn(
+                do_mine(
+                    keypair,
+                    MineParams {
+                        ethereum_address: a,
+                        address: *_address,
+                        priority_fee,
+                        runs,
+                        kind,
+                        delay,
+                        units,
+                        iterations,
+                        jito_tip,
+                        tippers: tippers.clone(),
+                    },
+                    txc)
+            );
+            // h.join().unwrap();
+            if automint > 0 {
+                let tx_clone1 = tx.clone();
+                let ws = ws_url.clone();
+                let _hm = thread::spawn(move || {
+                    let mut last_slot: Slot = 0;
+                    match PubsubClient::slot_subscribe(ws.as_str()) {
+                        Ok(subs) => {
+                            for slot in subs.1 {
+                                let txcm = tx_clone1.clone();
+                                let kpm = keypair_clone.insecure_clone();
+                                if slot.slot.sub(last_slot).ge(&(automint as u64)) {
+                                    last_slot = slot.slot;
+                                    do_mint(
+                                        kpm,
+                                        MintParams {
+                                            slot: slot.slot,
+                                            priority_fee,
+                                            kind,
+                                            automint,
+                                        },
+                                        txcm,
+                                    )
                                 }
                             }
-                            Err(e) => { println!("{:?}", e) }
-                        };
-                    });
-                }
+                        }
+                        Err(e) => { println!("{:?}", e) }
+                    };
+                });
             }
-            _ => ()
         };
     }
 
@@ -308,10 +261,11 @@ async fn main() {
         automint.to_string().green(),
     );
     println!(
-        "Running on RPC={}, fee={}, units={}",
+        "Running on RPC={}, fee={}, units={}, iterations={}",
         url.green(),
         priority_fee.to_string().green(),
         units.to_string().green(),
+        iterations.to_string().green(),
     );
 
     for msg in rx {
@@ -319,41 +273,6 @@ async fn main() {
     }
 }
 
-fn get_eth_record(client: &RpcClient, pda: &Pubkey) -> Option<UserEthXnRecord> {
-    let maybe_user_account_data_raw = client.get_account_data(&pda);
-    match maybe_user_account_data_raw {
-        Ok(user_account_data_raw) => {
-            let user_data: [u8; size_of::<UserEthXnRecord>() - 4] = user_account_data_raw.as_slice()[8..20].try_into().unwrap();
-            Some(UserEthXnRecord::try_from_slice(user_data.as_ref()).unwrap())
-        }
-        Err(_) => None
-    }
-}
-
-fn get_sol_record(client: &RpcClient, pda: &Pubkey) -> Option<UserSolXnRecord> {
-    let maybe_user_sol_account_data_raw = client.get_account_data(&pda);
-    match maybe_user_sol_account_data_raw {
-        Ok(user_sol_account_data_raw) => {
-            // 36 32 28
-            // println!("{} {}", user_sol_account_data_raw.len(), size_of::<UserSolXnRecord>());
-            let user_sol_data: [u8; size_of::<UserSolXnRecord>() - 4] = user_sol_account_data_raw.as_slice()[8..].try_into().unwrap();
-            Some(UserSolXnRecord::try_from_slice(user_sol_data.as_ref()).unwrap())
-        }
-        Err(_) => None
-    }
-}
-
-fn get_token_record(client: &RpcClient, pda: &Pubkey) -> Option<UserTokensRecord> {
-    let maybe_user_token_account_data_raw = client.get_account_data(&pda);
-    match maybe_user_token_account_data_raw {
-        Ok(user_balance_data_raw) => {
-            let user_data: [u8; size_of::<UserTokensRecord>()] = user_balance_data_raw.as_slice()[8..].try_into().unwrap();
-            Some(UserTokensRecord::try_from_slice(user_data.as_ref()).unwrap())
-        }
-        Err(_) => None
-    }
-}
-
 // Earn (mine) points by looking for hash patterns in randomized numbers
 async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
     let (
@@ -364,6 +283,7 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
         kind,
         _delay,
         units,
+        iterations,
         _jito_tip,
         _tippers
     ) = params.into();
@@ -372,83 +292,47 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
     let ws_url_ = str::replace(url.as_str(), "http", "ws");
     let ws_url = str::replace(ws_url_.as_str(), "8899", "8900");
 
-    let miners_program_ids_str = std::env::var("MINERS").unwrap_or(String::from(MINERS));
-    let miners = miners_program_ids_str.split(',').collect::<Vec<&str>>();
-    assert_eq!(miners.len(), MAX_MINERS as usize, "Bad miners set");
-
-    let program_id = Pubkey::try_from(miners[kind as usize]).expect("Bad program ID");
+    let program_id = sol_xen_sdk::miner_program_id();
 
-    tx.send(format!("{Y}[{}]{U} Miner Program ID={}", kind.to_string(), program_id.to_string().green())).unwrap();
+    tx.send(format!("{Y}[{}]{U} Miner Program ID={}", kind, program_id.to_string().green())).unwrap();
 
     let legacy_client = RpcClient::new(url.clone());
     let non_blocking_client = NonblockingRpcClient::new(url.clone());
 
     tx.send(format!(
         "{Y}[{}]{U} Using user wallet={}, account={}",
-        kind.to_string(),
+        kind,
         payer.pubkey().to_string().green(),
         ethereum_address.green(),
     )).unwrap();
 
-    let (global_xn_record_pda, _global_bump) = Pubkey::find_program_address(
-        &[b"xn-miner-global", kind.to_be_bytes().as_slice()],
-        &program_id,
-    );
-    // tx.send(format!("Global XN PDA: {}", global_xn_record_pda.to_string().green())).unwrap();
-
-    let (user_eth_xn_record_pda, _user_eth_bump) = Pubkey::find_program_address(
-        &[
-            b"xn-by-eth",
-            &address.as_slice(),
-            kind.to_be_bytes().as_slice(),
-            program_id.as_ref()
-        ],
-        &program_id,
-    );
+    let (user_eth_xn_record_pda, _user_eth_bump) = pda::user_eth_xn_record(&address, kind, &program_id);
     // tx.send(format!("User Eth PDA: {}", user_eth_xn_record_pda.to_string().green())).unwrap();
 
-    let (user_sol_xn_record_pda, _user_sol_bump) = Pubkey::find_program_address(
-        &[
-            b"xn-by-sol",
-            &payer.pubkey().to_bytes(),
-            kind.to_be_bytes().as_slice(),
-            program_id.as_ref()
-        ],
-        &program_id,
-    );
+    let (user_sol_xn_record_pda, _user_sol_bump) = pda::user_sol_xn_record(&payer.pubkey(), kind, &program_id);
     // tx.send(format!("User Sol PDA: {}", user_sol_xn_record_pda.to_string().green())).unwrap();
 
-    let method_name_data = "global:mine_hashes";
-    let digest = hash(method_name_data.as_bytes());
-    let ix_data = &digest.to_bytes()[0..8];
     let blockhash = non_blocking_client.get_latest_blockhash().await.expect("No blockhash");
     let compute_budget_instruction_price = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
     let compute_budget_instruction_limit = ComputeBudgetInstruction::set_compute_unit_limit(units);
     
     let tpu_rpc_client = Arc::new(non_blocking_client);
 
+    if sol_xen_sdk::fetch_remaining_cycles(&legacy_client, kind, &program_id) == Some(0) {
+        tx.send(format!("{Y}[{}]{U} {}", kind, "Mining is over; nothing to do".red())).unwrap();
+        return;
+    }
+
     loop {
         let messages = (0..runs)
             .map(|_i| {
-                let address_str = ethereum_address.clone();
-                let mint_hashes = MineHashes {
-                    eth_account: EthAccount {
-                        address,
-                        address_str,
-                    },
-                    _kind: kind,
-                };
-                let mine_instruction = Instruction {
-                    program_id,
-                    data: [ix_data, to_vec(&mint_hashes).unwrap().as_slice()].concat().to_vec(),
-                    accounts: vec![
-                        AccountMeta::new(global_xn_record_pda, false),
-                        AccountMeta::new(user_eth_xn_record_pda, false),
-                        AccountMeta::new(user_sol_xn_record_pda, false),
-                        AccountMeta::new(payer.pubkey(), true),
-                        AccountMeta::new_readonly(system_program::ID, false),
-                    ],
-                };
+                let mine_instruction = sol_xen_sdk::mine_hashes_v2(
+                    &program_id,
+                    &payer.pubkey(),
+                    address,
+                    kind,
+                    iterations,
+                );
                 let instructions = vec![
                     compute_budget_instruction_limit.clone(),
                     compute_budget_instruction_price.clone(),
@@ -506,14 +390,14 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
             .filter(|e| (**e).is_some())
             .count();
 
-        let user_state = get_eth_record(&legacy_client, &user_eth_xn_record_pda);
-        let user_sol_state = get_sol_record(&legacy_client, &user_sol_xn_record_pda);
+        let user_state = get_account::<UserEthXnRecord>(&legacy_client, &user_eth_xn_record_pda);
+        let user_sol_state = get_account::<UserSolXnRecord>(&legacy_client, &user_sol_xn_record_pda);
 
         let (h, sh) = user_state.map(|s| (s.hashes.to_string(), s.superhashes.to_string()))
             .unwrap_or((String::from("-"), String::from("-")));
         tx.send(format!(
             "{Y}[{}]{U} Txs: {} Errors: {} Hashes={}, Superhashes={}, Points={}",
-            kind.to_string(),
+            kind,
             oks.to_string().yellow(),
             errs.to_string().yellow(),
             h.yellow(),
@@ -521,6 +405,14 @@ async fn do_mine(payer: Keypair, params: MineParams, tx: mpsc::Sender<String>) {
             user_sol_state.map(|s| (s.points / DECIMALS).to_string())
                 .unwrap_or(String::from("-")).yellow(),
         )).unwrap();
+
+        let mining_is_over = transaction_errors.iter()
+            .flatten()
+            .any(|e| sol_xen_sdk::is_custom_error(e, sol_xen_sdk::MINING_IS_OVER));
+        if mining_is_over {
+            tx.send(format!("{Y}[{}]{U} {}", kind, "Mining is over; stopping".red())).unwrap();
+            break;
+        }
     }
 }
 
@@ -529,14 +421,8 @@ fn do_mint(payer: Keypair, params: MintParams, tx: mpsc::Sender<String>) {
     let (slot, priority_fee, kind, _) = params.into();
     let url = std::env::var("ANCHOR_PROVIDER_URL").expect("ANCHOR_PROVIDER_URL must be set.");
 
-    let program_id_minter_str = std::env::var("PROGRAM_ID_MINTER").unwrap_or(String::from(MINTER));
-    let program_id_minter = Pubkey::try_from(program_id_minter_str.as_str()).expect("Bad program ID");
-
-    let miners_program_ids_str = std::env::var("MINERS").unwrap_or(String::from(MINERS));
-    let miners = miners_program_ids_str.split(',').collect::<Vec<&str>>();
-    assert_eq!(miners.len(), MAX_MINERS as usize, "Bad miners set");
-
-    let program_id_miner = Pubkey::try_from(miners[kind as usize]).expect("Bad program ID");
+    let program_id_minter = sol_xen_sdk::minter_program_id();
+    let program_id_miner = sol_xen_sdk::miner_program_id();
 
     // println!("Test{:?}", kind.to_be_bytes().as_slice());
     // tx.send(format!(
@@ -549,74 +435,31 @@ fn do_mint(payer: Keypair, params: MintParams, tx: mpsc::Sender<String>) {
 
     // println!("Using user wallet={}, fee={}", payer.pubkey().to_string().green(), priority_fee.to_string().green(), );
 
-    let (user_sol_xn_record_pda, _user_bump) = Pubkey::find_program_address(
-        &[
-            b"xn-by-sol",
-            &payer.pubkey().to_bytes(),
-            kind.to_be_bytes().as_slice(),
-            &program_id_miner.to_bytes()
-        ],
-        &program_id_miner,
-    );
-    // println!("User record PDA={} bump={}", user_sol_xn_record_pda.to_string().green(), _user_bump.to_string());
-
-    let (user_token_record_pda, _user_rec_bump) = Pubkey::find_program_address(
-        &[
-            b"sol-xen-minted",
-            &payer.pubkey().to_bytes(),
-        ],
-        &program_id_minter,
-    );
+    let (user_token_record_pda, _user_rec_bump) = pda::user_tokens_record(&payer.pubkey(), &program_id_minter);
     // println!("User token record PDA={} bump={}", user_token_record_pda.to_string().green(), _user_rec_bump.to_string());
 
-    let (mint_pda, _mint_bump) = Pubkey::find_program_address(
-        &[b"mint"],
-        &program_id_minter,
-    );
-    // println!("Mint PDA={}", mint_pda.to_string().green());
-
-    let associate_token_program = Pubkey::try_from("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
-
-    let user_token_account = get_associated_token_address_with_program_id(
-        &payer.pubkey(),
-        &mint_pda,
-        &spl_token::ID,
-    );
+    // migrations and legacy imports go first, in txs of their own
+    if let Err(err) = sol_xen_sdk::prepare_mint_all(&client, &program_id_miner, &payer, priority_fee) {
+        tx.send(format!("{R}[{}]{U} Unable to import legacy points: {:?}", kind, err)).unwrap();
+        return;
+    }
 
-    let method_name_data = "global:mint_tokens";
-    let digest = hash(method_name_data.as_bytes());
-    let ix_data = &digest.to_bytes()[0..8];
-
-    let instruction = Instruction {
-        program_id: program_id_minter,
-        data: [
-            ix_data,
-            kind.to_be_bytes().as_slice()
-        ].concat().to_vec(),
-        accounts: vec![
-            AccountMeta::new_readonly(user_sol_xn_record_pda, false),
-            AccountMeta::new(user_token_record_pda, false),
-            AccountMeta::new(user_token_account, false),
-            AccountMeta::new_readonly(payer.pubkey(), true),
-            AccountMeta::new(mint_pda, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
-            AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(associate_token_program, false),
-            AccountMeta::new_readonly(program_id_miner, false),
-        ],
-    };
+    // the wallet's points of all kinds are converted at once
+    let instructions = sol_xen_sdk::mint_all_instructions(&client, &program_id_miner, &program_id_minter, &payer.pubkey());
 
     // get pre-tx user balance
-    let user_token_state_pre = get_token_record(&client, &user_token_record_pda);
+    let user_token_state_pre = get_account::<UserTokensRecord>(&client, &user_token_record_pda);
 
     let compute_budget_instruction_price = ComputeBudgetInstruction::set_compute_unit_price(priority_fee);
 
     let transaction = Transaction::new_signed_with_payer(
         &[
-            // compute_budget_instruction_limit,
-            compute_budget_instruction_price,
-            instruction.clone()
-        ],
+            vec![
+                // compute_budget_instruction_limit,
+                compute_budget_instruction_price,
+            ],
+            instructions,
+        ].concat(),
         Some(&payer.pubkey()),
         &[&payer],
         client.get_latest_blockhash().unwrap(),
@@ -626,35 +469,40 @@ fn do_mint(payer: Keypair, params: MintParams, tx: mpsc::Sender<String>) {
 
     match result {
         Ok(signature) => {
-            // let user_sol_state = get_sol_record(&client, &user_sol_xn_record_pda);
-            let user_token_state = get_token_record(&client, &user_token_record_pda);
+            // let user_sol_state = get_account::<UserSolXnRecord>(&client, &user_sol_xn_record_pda);
+            let user_token_state = get_account::<UserTokensRecord>(&client, &user_token_record_pda);
             let user_token_state_1 = user_token_state.clone();
             let user_token_state_2 = user_token_state.clone();
             let delta = user_token_state_pre
                 .map(|s_pre| user_token_state
                     .map(|s| s.tokens_minted - s_pre.tokens_minted)
                 ).unwrap_or(Some(0)).unwrap_or(0);
-            let delta_str: String = if delta > 0 { format!("(+{})", delta / DECIMALS) } else { String::from("") };
+            let delta_str: String = if delta > 0 { format!("(+{})", delta as f64 / TOKEN_DECIMALS as f64) } else { String::from("") };
             let points = user_token_state_1
                 .map(|s| s.points_counters
                     .map(|p| (p / DECIMALS).to_string()).join(",")).unwrap_or(String::from(""));
 
+            let converted = user_token_state_2.as_ref()
+                .map(|s| (s.points_converted / DECIMALS).to_string())
+                .unwrap_or(String::from(""));
+
             // AM #2: balance @slot=150279: points=0,3600,3600,0, tokens=7200(+5400). Total supply=7200
             tx.send(format!(
-                "{G}[{}]{U} Mint Tx={}, Slot={} Points={} Tokens={}{}",
-                kind.to_string(),
+                "{G}[{}]{U} Mint Tx={}, Slot={} Points={} Converted={} Tokens={}{}",
+                kind,
                 signature.to_string().green(),
                 slot.to_string().green(),
                 points.green(),
+                converted.green(),
                 user_token_state_2
-                    .map(|s| (s.tokens_minted / DECIMALS).to_string())
+                    .map(|s| (s.tokens_minted as f64 / TOKEN_DECIMALS as f64).to_string())
                     .unwrap_or(String::from("")).green(),
                 delta_str.yellow(),
             )).unwrap()
         }
         Err(_err) => tx.send(format!(
             "{R}[{}]{U} Unable to confirm Mint tx due to timeout",
-            kind.to_string()
+            kind
         )).unwrap(),
     };
 }
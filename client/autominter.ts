@@ -7,7 +7,7 @@ import {ComputeBudgetProgram, PublicKey, TransactionExpiredTimeoutError} from "@
 import fs from "node:fs";
 import path from "node:path";
 import dotenv from "dotenv";
-import {getMint, TOKEN_PROGRAM_ID} from "@solana/spl-token";
+import {getAssociatedTokenAddressSync, getMint} from "@solana/spl-token";
 import BN from "bn.js";
 
 dotenv.config();
@@ -17,6 +17,9 @@ const Y = '\x1b[33m';
 const U = '\x1b[39m';
 
 const decimals = new BN(1_000_000_000);
+const tokenDecimals = new BN(1_000_000);
+// UserTokensRecord size before sub-unit remainders were carried over
+const LEGACY_TOKENS_RECORD_LEN = 8 + 80;
 
 export type AutoMinterParams = {
     kind: number;
@@ -72,12 +75,18 @@ const [mint] = web3.PublicKey.findProgramAddressSync(
     program.programId
 );
 
-const mintAccount = await getMint(provider.connection, mint);
+// SPL Token or Token-2022, whichever the mint was created with
+const tokenProgram = (await connection.getAccountInfo(mint))!.owner;
+const mintAccount = await getMint(provider.connection, mint, undefined, tokenProgram);
 
-const userTokenAccount = utils.token.associatedAddress({
-    mint: mintAccount.address,
-    owner: wallet.publicKey
-})
+// mints go to the token account of the user's payout recipient, if they've set one
+const [payoutAccount] = web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("xn-payout"), wallet.publicKey.toBuffer()],
+    program.programId
+);
+const payout = await program.account.payoutRecord.fetchNullable(payoutAccount);
+const recipient = payout?.recipient ?? wallet.publicKey;
+const userTokenAccount = getAssociatedTokenAddressSync(mintAccount.address, recipient, false, tokenProgram);
 
 let currentSlot = startSlot;
 connection.onSlotChange(async ({ slot }) => {
@@ -88,6 +97,13 @@ connection.onSlotChange(async ({ slot }) => {
             microLamports: priorityFee
         });
 
+        // records last minted to before remainders were carried over have an older layout, to be migrated first
+        const legacyRecord = await connection.getAccountInfo(userTokenRecordAccount)
+            .then(info => info?.data.length === LEGACY_TOKENS_RECORD_LEN);
+        const migrateTokensRecord = legacyRecord
+            ? [await program.methods.migrateTokensRecord(wallet.publicKey).accounts({payer: wallet.publicKey}).instruction()]
+            : [];
+
         // const totalSupplyPre = await connection.getTokenSupply(mintAccount.address);
         let userTokensRecordPre: any = null;
         try {
@@ -102,16 +118,17 @@ connection.onSlotChange(async ({ slot }) => {
             user: wallet.publicKey,
             mintAccount: mintAccount.address,
             userTokenAccount,
+            recipient,
             userRecord: userSolXnRecordAccount,
             userTokenRecord: userTokenRecordAccount,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            tokenProgram,
             associateTokenProgram,
             minerProgram: new PublicKey(minerProgramId)
         };
         program.methods.mintTokens(kind)
             .accounts(mintAccounts)
             .signers([wallet.payer])
-            .preInstructions([addPriorityFee])
+            .preInstructions([addPriorityFee, ...migrateTokensRecord])
             .rpc({ commitment: "confirmed" })
             .then((_) => {
                 return connection.getTokenSupply(mintAccount.address)
@@ -119,11 +136,11 @@ connection.onSlotChange(async ({ slot }) => {
                         return program.account.userTokensRecord.fetch(userTokenRecordAccount)
                             .then(userTokensRecord => {
                                 const delta = userTokensRecordPre
-                                    ? (userTokensRecord.tokensMinted.sub(userTokensRecordPre.tokensMinted).div(decimals)).toNumber()
+                                    ? (userTokensRecord.tokensMinted.sub(userTokensRecordPre.tokensMinted)).toNumber() / tokenDecimals.toNumber()
                                     : 0;
                                 const deltaStr = delta > 0 ? `${Y}(+${delta})${U}` : '';
                                 const counters = userTokensRecord.pointsCounters.map(c => c.div(decimals).toNumber());
-                                parentPort?.postMessage(`AM #${threadId}: balance @slot=${Y}${currentSlot}${U}: points=${G}${counters}${U}, tokens=${G}${userTokensRecord.tokensMinted.div(decimals).toNumber()}${U}${deltaStr}. Total supply=${G}${totalSupply.value.uiAmount}${U}`)
+                                parentPort?.postMessage(`AM #${threadId}: balance @slot=${Y}${currentSlot}${U}: points=${G}${counters}${U}, converted=${G}${userTokensRecord.pointsConverted.div(decimals).toNumber()}${U}, tokens=${G}${userTokensRecord.tokensMinted.toNumber() / tokenDecimals.toNumber()}${U}${deltaStr}. Total supply=${G}${totalSupply.value.uiAmount}${U}`)
                             })
                     })
             })
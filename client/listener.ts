@@ -1,8 +1,6 @@
 import dotenv from "dotenv";
-import {SolXenMiner as SolXenMiner0} from '../target/types/sol_xen_miner_0';
-import {SolXenMiner as SolXenMiner1} from '../target/types/sol_xen_miner_1';
-import {SolXenMiner as SolXenMiner2} from '../target/types/sol_xen_miner_2';
-import {SolXenMiner as SolXenMiner3} from '../target/types/sol_xen_miner_3';
+import {SolXenMiner} from '../target/types/sol_xen_miner';
+import {SolXenMinter} from '../target/types/sol_xen_minter';
 import {AnchorProvider, setProvider, Program, web3, workspace} from '@coral-xyz/anchor';
 //import { debug } from 'debug';
 import pkg from 'debug';
@@ -26,41 +24,51 @@ async function main() {
         null as any,
     )
     setProvider(provider);
-    const program0 = workspace.SolXenMiner0 as Program<SolXenMiner0>;
-    const program1 = workspace.SolXenMiner1 as Program<SolXenMiner1>;
-    const program2 = workspace.SolXenMiner2 as Program<SolXenMiner2>;
-    const program3 = workspace.SolXenMiner3 as Program<SolXenMiner3>;
+    const miner = workspace.SolXenMiner as Program<SolXenMiner>;
+    const minter = workspace.SolXenMinter as Program<SolXenMinter>;
 
-    let listener0: number, listener1: number, listener2: number, listener3: number;
-
-    const onHashEvent = (miner: number) => (event: any, slot: number) => {
-        const { user, ethAccount, hashes, superhashes, points } = event;
+    const onHashEvent = (event: any, slot: number) => {
+        const { kind, user, ethAccount, amp, hashes, superhashes, points, userPoints } = event;
         const account = Buffer.from(ethAccount).toString("hex");
-        const minted = points / 1_000_000_000;
-        log(`Event: miner=${Y}${miner}${U} slot=${Y}${slot.toString()}${U}, user=${Y}${user.toBase58()}${U}, account=${Y}${account}${U}, hashes=${Y}${hashes}${U}, superhashes=${Y}${superhashes}${U}, minted=${Y}${minted}${U}`);
+        const minted = Number(points.toString()) / 1_000_000_000;
+        const total = Number(userPoints.toString()) / 1_000_000_000;
+        log(`Hashes: kind=${Y}${kind}${U} slot=${Y}${slot.toString()}${U}, user=${Y}${user.toBase58()}${U}, account=${Y}${account}${U}, amp=${Y}${amp}${U}, hashes=${Y}${hashes}${U}, superhashes=${Y}${superhashes}${U}, minted=${Y}${minted}${U}, total=${Y}${total}${U}`);
+    }
+
+    const onAmpDecayed = (event: any) => {
+        const { kind, slot, amp } = event;
+        log(`AMP decayed: kind=${Y}${kind}${U} slot=${Y}${slot.toString()}${U}, amp=${Y}${amp}${U}`);
     }
 
+    const onTotalsCranked = (event: any) => {
+        const { kind, slot, shards, globalHashes, globalSuperhashes } = event;
+        log(`Totals cranked: kind=${Y}${kind}${U} slot=${Y}${slot.toString()}${U}, shards=${Y}${shards}${U}, hashes=${Y}${globalHashes.toString()}${U}, superhashes=${Y}${globalSuperhashes.toString()}${U}`);
+    }
+
+    const onMinerInitialized = (event: any) => {
+        const { kind, admin, slot } = event;
+        log(`Miner initialized: kind=${Y}${kind}${U} slot=${Y}${slot.toString()}${U}, admin=${Y}${admin.toBase58()}${U}`);
+    }
+
+    const onTokensMinted = (event: any, slot: number) => {
+        const { kind, user, amount, tokensMinted } = event;
+        log(`Tokens minted: kind=${Y}${kind}${U} slot=${Y}${slot.toString()}${U}, user=${Y}${user.toBase58()}${U}, amount=${Y}${amount.toString()}${U}, total=${Y}${tokensMinted.toString()}${U}`);
+    }
+
+    const listeners = [
+        miner.addEventListener("hashEventV2", onHashEvent),
+        miner.addEventListener("ampDecayed", onAmpDecayed),
+        miner.addEventListener("totalsCranked", onTotalsCranked),
+        miner.addEventListener("minerInitialized", onMinerInitialized),
+    ];
+    const minterListener = minter.addEventListener("tokensMinted", onTokensMinted);
+
     process.addListener("SIGINT", () => {
-        if (listener0) {
-            program0.removeEventListener(listener0);
-        }
-        if (listener1) {
-            program0.removeEventListener(listener1);
-        }
-        if (listener2) {
-            program0.removeEventListener(listener2);
-        }
-        if (listener3) {
-            program0.removeEventListener(listener3);
-        }
+        listeners.forEach(listener => miner.removeEventListener(listener));
+        minter.removeEventListener(minterListener);
         process.exit(0)
     })
 
-    listener0 = program0.addEventListener("hashEvent", onHashEvent(0));
-    listener1 = program1.addEventListener("hashEvent", onHashEvent(1));
-    listener2 = program2.addEventListener("hashEvent", onHashEvent(2));
-    listener3 = program3.addEventListener("hashEvent", onHashEvent(3));
-
     // prevent the script from exiting
     while (true) {
         await new Promise(resolve => setTimeout(resolve, 500));
@@ -25,6 +25,10 @@ const G = '\x1b[32m';
 const Y = '\x1b[33m';
 const U = '\x1b[39m';
 
+// compute unit limit of a tx, and hashes per tx fitting in it (MAX_TX_UNITS and MAX_ITERATIONS in the SDK)
+const MAX_TX_UNITS = 1_400_000;
+const MAX_ITERATIONS = 86;
+
 async function main() {
     // PARSE CLI ARGS
 
@@ -32,7 +36,8 @@ async function main() {
     let cmd: Cmd;
     let address: string = '';
     let priorityFee: number = 1;
-    let units: number = 1_180_000;
+    let units: number;
+    let iterations: number = 72;
     let runs: number = 1;
     let kind: number;
     let delay: number = 1;
@@ -49,8 +54,13 @@ async function main() {
         .option('units', {
             alias: 'u',
             type: 'number',
-            default: 1_400_000,
-            description: 'Solana MAX Compute Units'
+            description: 'Solana MAX Compute Units (derived from iterations by default)'
+        })
+        .option('iterations', {
+            alias: 'i',
+            type: 'number',
+            default: 72,
+            description: 'Hashes per tx, up to the kind\'s max_hashes and 86 (tx compute limit)'
         })
         .option('address', {
             alias: 'addr',
@@ -103,8 +113,19 @@ async function main() {
         priorityFee = Number(yArgs.priorityFee)
     }
 
-    if (yArgs.units) {
-        units = Number(yArgs.units)
+    if (yArgs.iterations) {
+        iterations = Number(yArgs.iterations)
+        if (iterations < 1 || iterations > MAX_ITERATIONS) {
+            console.log(`Wrong iterations, 1...${MAX_ITERATIONS}`)
+            process.exit(1)
+        }
+    }
+
+    // base cost of mine_hashes plus each hash iteration, see MINE_HASHES_*_UNITS in the SDK
+    units = yArgs.units ? Number(yArgs.units) : 100_000 + 15_000 * iterations
+    if (units > MAX_TX_UNITS) {
+        console.log(`Wrong units, up to ${MAX_TX_UNITS}`)
+        process.exit(1)
     }
 
     if (yArgs.runs) {
@@ -128,9 +149,10 @@ async function main() {
         }
     }
 
-    const minersStr = process.env.MINERS || 'B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN,2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ,5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ,DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S';
+    // all kinds are served by a single miner program
+    const minerStr = process.env.PROGRAM_ID_MINER || '9vCHCeZcC2yqgLwbECtNDzVEi3opBWdYeGKuJ19z14th';
 
-    const miners = minersStr.split(',').map(s => new web3.PublicKey(s));
+    const miners = [0, 1, 2, 3].map(() => new web3.PublicKey(minerStr));
     const programId = miners[kind];
 
     // SETUP SOLANA ENVIRONMENT
@@ -205,6 +227,16 @@ async function main() {
         programId
     );
 
+    // same as `shard_index` in the miner program
+    const [xnShardAddress] = web3.PublicKey.findProgramAddressSync(
+        [
+            Buffer.from("xn-miner-shard"),
+            Buffer.from([kind]),
+            Buffer.from([user.publicKey.toBytes()[0] % 16]),
+        ],
+        programId
+    );
+
     const ethAddress20 = Buffer.from(address.slice(2), 'hex')
     const [userEthXnRecordAccount] = web3.PublicKey.findProgramAddressSync(
         [
@@ -242,7 +274,7 @@ async function main() {
     } else if (cmd === Cmd.Mine) {
 
         console.log(`Running miner with params: address=${G}${address}${U}, priorityFee=${G}${priorityFee}${U}, runs=${G}${runs}${U}, delay=${G}${delay}${U}`);
-        console.log(`Using CU max=${G}${units}${U}`);
+        console.log(`Using CU max=${G}${units}${U}, iterations=${G}${iterations}${U}`);
         const modifyComputeUnits = ComputeBudgetProgram.setComputeUnitLimit({
             units
         });
@@ -262,13 +294,15 @@ async function main() {
                 xnByEth: userEthXnRecordAccount,
                 xnBySol: userSolXnRecordAccount,
                 globalXnRecord: globalXnRecordAddress,
+                xnShard: xnShardAddress,
                 programId
             };
-            const ethAddr = { address: Array.from(ethAddress20), addressStr: address };
+            // the address was parsed and checksummed by getAddress, mine_hashes_v2 takes the raw bytes only
+            const ethAddr = Array.from(ethAddress20);
             try {
                 process.stdout.write(`[ ] Waiting for tx\r`);
                 currentRun++;
-                const mintTx = await program.methods.mineHashes(ethAddr, kind)
+                const mintTx = await program.methods.mineHashesV2(ethAddr, kind, iterations)
                     .accounts(mintAccounts)
                     .signers([user])
                     .preInstructions([modifyComputeUnits, addPriorityFee])
@@ -5,7 +5,7 @@ import { ComputeBudgetProgram, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.
 import { AnchorProvider, setProvider, web3, Wallet, workspace, utils } from '@coral-xyz/anchor';
 import * as fs from "node:fs";
 import path from "node:path";
-import { getMint, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { getAssociatedTokenAddressSync, getMint } from "@solana/spl-token";
 import BN from "bn.js";
 dotenv.config();
 var Cmd;
@@ -17,6 +17,9 @@ const G = '\x1b[32m';
 const Y = '\x1b[33m';
 const U = '\x1b[39m';
 const decimals = new BN(1_000_000_000);
+const tokenDecimals = new BN(1_000_000);
+// UserTokensRecord size before sub-unit remainders were carried over
+const LEGACY_TOKENS_RECORD_LEN = 8 + 80;
 async function main() {
     // PARSE CLI ARGS
     const [, , , ...params] = process.argv;
@@ -71,8 +74,9 @@ async function main() {
         process.exit(1);
     }
     // SETUP SOLANA ENVIRONMENT
-    const minersStr = process.env.MINERS || 'B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN,2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ,5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ,DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S';
-    const miners = minersStr.split(',').map(s => new web3.PublicKey(s));
+    // all kinds are served by a single miner program
+    const minerStr = process.env.PROGRAM_ID_MINER || '9vCHCeZcC2yqgLwbECtNDzVEi3opBWdYeGKuJ19z14th';
+    const miners = [0, 1, 2, 3].map(() => new web3.PublicKey(minerStr));
     const minerProgramId = miners[kind];
     const network = process.env.ANCHOR_PROVIDER_URL || 'localnet';
     console.log(`\nRunning on ${G}${network}${U}`);
@@ -126,46 +130,64 @@ async function main() {
         user.publicKey.toBuffer(),
     ], program.programId);
     const [mint] = web3.PublicKey.findProgramAddressSync([Buffer.from("mint")], program.programId);
-    const mintAccount = await getMint(provider.connection, mint);
-    const userTokenAccount = utils.token.associatedAddress({
-        mint: mintAccount.address,
-        owner: user.publicKey
-    });
+    // SPL Token or Token-2022, whichever the mint was created with
+    const tokenProgram = (await connection.getAccountInfo(mint)).owner;
+    const mintAccount = await getMint(provider.connection, mint, undefined, tokenProgram);
+    // mints go to the token account of the user's payout recipient, if they've set one
+    const [payoutAccount] = web3.PublicKey.findProgramAddressSync([Buffer.from("xn-payout"), user.publicKey.toBuffer()], program.programId);
+    const payout = await program.account.payoutRecord.fetchNullable(payoutAccount);
+    const recipient = payout?.recipient ?? user.publicKey;
+    const userTokenAccount = getAssociatedTokenAddressSync(mintAccount.address, recipient, false, tokenProgram);
     let currentSlot = await connection.getSlot('confirmed');
+    const legacyTokensRecordMigration = async () => {
+        const info = await connection.getAccountInfo(userTokenRecordAccount);
+        if (info?.data.length !== LEGACY_TOKENS_RECORD_LEN) {
+            return [];
+        }
+        return [await program.methods.migrateTokensRecord(user.publicKey).accounts({ payer: user.publicKey }).instruction()];
+    };
+
     const doMint = async () => {
         const addPriorityFee = ComputeBudgetProgram.setComputeUnitPrice({
             microLamports: priorityFee
         });
         const totalSupplyPre = await connection.getTokenSupply(mintAccount.address);
-        const userTokensRecordPre = await program.account.userTokensRecord.fetch(userTokenRecordAccount);
+        // records last minted to before remainders were carried over have an older layout, to be migrated first
+        const migrateTokensRecord = await legacyTokensRecordMigration();
+        const userTokensRecordPre = migrateTokensRecord.length > 0
+            ? null
+            : await program.account.userTokensRecord.fetch(userTokenRecordAccount);
         const associateTokenProgram = new web3.PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
         const mintAccounts = {
             user: user.publicKey,
             mintAccount: mintAccount.address,
             userTokenAccount,
+            recipient,
             userRecord: userSolXnRecordAccount,
             userTokenRecord: userTokenRecordAccount,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            tokenProgram,
             associateTokenProgram,
             minerProgram: new PublicKey(minerProgramId)
         };
         const mintTx = await program.methods.mintTokens(kind)
             .accounts(mintAccounts)
             .signers([user])
-            .preInstructions([addPriorityFee])
+            .preInstructions([addPriorityFee, ...migrateTokensRecord])
             .rpc({ commitment: "confirmed" });
         const totalSupply = await connection.getTokenSupply(mintAccount.address);
         const userTokensRecord = await program.account.userTokensRecord.fetch(userTokenRecordAccount);
-        const delta = (userTokensRecord.tokensMinted.sub(userTokensRecordPre.tokensMinted).div(decimals)).toNumber();
+        const delta = userTokensRecordPre
+            ? userTokensRecord.tokensMinted.sub(userTokensRecordPre.tokensMinted).toNumber() / tokenDecimals.toNumber()
+            : 0;
         const deltaStr = delta > 0 ? `${Y}(+${delta})${U}` : '';
         const counters = userTokensRecord.pointsCounters.map(c => c.div(decimals).toNumber());
-        console.log(`User balance @slot=${Y}${currentSlot}${U}: points=${G}${counters}${U}, tokens=${G}${userTokensRecord.tokensMinted.div(decimals).toNumber()}${U}${deltaStr}. Total supply=${G}${totalSupply.value.uiAmount}${U}`);
+        console.log(`User balance @slot=${Y}${currentSlot}${U}: points=${G}${counters}${U}, converted=${G}${userTokensRecord.pointsConverted.div(decimals).toNumber()}${U}, tokens=${G}${userTokensRecord.tokensMinted.toNumber() / tokenDecimals.toNumber()}${U}${deltaStr}. Total supply=${G}${totalSupply.value.uiAmount}${U}`);
     };
     // PROCESS COMMANDS
     if (cmd === Cmd.Balance) {
         const totalSupply = await connection.getTokenSupply(mintAccount.address);
         const userTokensRecord = await program.account.userTokensRecord.fetch(userTokenRecordAccount);
-        console.log(`User balance: points=${G}${userTokensRecord.pointsCounters}${U}, tokens=${G}${userTokensRecord.tokensMinted}${U}, supply=${G}${totalSupply.value.uiAmount}${U}`);
+        console.log(`User balance: points=${G}${userTokensRecord.pointsCounters}${U}, converted=${G}${userTokensRecord.pointsConverted}${U}, tokens=${G}${userTokensRecord.tokensMinted}${U} (base units), carried=${G}${userTokensRecord.pointsRemainder}${U}, supply=${G}${totalSupply.value.uiAmount}${U}`);
     }
     else if (cmd === Cmd.Mint && autoMint === 0 /* auto-mint disabled */) {
         console.log(`Running single mint with params: priorityFee=${G}${priorityFee}${U}`);
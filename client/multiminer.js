@@ -16,11 +16,22 @@ var Cmd;
 const G = '\x1b[32m';
 const Y = '\x1b[33m';
 const U = '\x1b[39m';
+
+// compute unit limit of a tx, and hashes per tx fitting in it (MAX_TX_UNITS and MAX_ITERATIONS in the SDK)
+const MAX_TX_UNITS = 1_400_000;
+const MAX_ITERATIONS = 86;
+
 export const getPDAs = ({ programId, kind, address, wallet }) => {
     const [globalXnRecordAddress] = web3.PublicKey.findProgramAddressSync([
         Buffer.from("xn-miner-global"),
         Buffer.from([kind]),
     ], programId);
+    // same as `shard_index` in the miner program
+    const [xnShardAddress] = web3.PublicKey.findProgramAddressSync([
+        Buffer.from("xn-miner-shard"),
+        Buffer.from([kind]),
+        Buffer.from([wallet.publicKey.toBytes()[0] % 16]),
+    ], programId);
     const ethAddress20 = Buffer.from(address.slice(2), 'hex');
     const [userEthXnRecordAccount] = web3.PublicKey.findProgramAddressSync([
         Buffer.from("xn-by-eth"),
@@ -36,6 +47,7 @@ export const getPDAs = ({ programId, kind, address, wallet }) => {
     ], programId);
     return {
         globalXnRecordAddress,
+        xnShardAddress,
         userEthXnRecordAccount,
         userSolXnRecordAccount,
         ethAddress20,
@@ -48,7 +60,8 @@ async function main() {
     let cmd;
     let address = zeroAddress;
     let priorityFee = 1;
-    let units = 1_180_000;
+    let units;
+    let iterations = 72;
     let runs = null;
     let kind = null;
     let delay = 1;
@@ -65,8 +78,13 @@ async function main() {
         .option('units', {
         alias: 'u',
         type: 'number',
-        default: 1_400_000,
-        description: 'Solana MAX Compute Units'
+        description: 'Solana MAX Compute Units (derived from iterations by default)'
+    })
+        .option('iterations', {
+        alias: 'i',
+        type: 'number',
+        default: 72,
+        description: 'Hashes per tx, up to the kind\'s max_hashes and 86 (tx compute limit)'
     })
         .option('address', {
         alias: 'addr',
@@ -115,8 +133,19 @@ async function main() {
     if (yArgs.priorityFee) {
         priorityFee = Number(yArgs.priorityFee);
     }
-    if (yArgs.units) {
-        units = Number(yArgs.units);
+    if (yArgs.iterations) {
+        iterations = Number(yArgs.iterations);
+        if (iterations < 1 || iterations > MAX_ITERATIONS) {
+            console.log(`Wrong iterations, 1...${MAX_ITERATIONS}`);
+            process.exit(1);
+        }
+    }
+
+    // base cost of mine_hashes plus each hash iteration, see MINE_HASHES_*_UNITS in the SDK
+    units = yArgs.units ? Number(yArgs.units) : 100_000 + 15_000 * iterations;
+    if (units > MAX_TX_UNITS) {
+        console.log(`Wrong units, up to ${MAX_TX_UNITS}`);
+        process.exit(1);
     }
     if (yArgs.runs) {
         runs = Number(yArgs.runs);
@@ -140,8 +169,9 @@ async function main() {
             process.exit(1);
         }
     }
-    const minersStr = process.env.MINERS || 'B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN,2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ,5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ,DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S';
-    const miners = minersStr.split(',').map(s => new web3.PublicKey(s));
+    // all kinds are served by a single miner program
+    const minerStr = process.env.PROGRAM_ID_MINER || '9vCHCeZcC2yqgLwbECtNDzVEi3opBWdYeGKuJ19z14th';
+    const miners = [0, 1, 2, 3].map(() => new web3.PublicKey(minerStr));
     // SETUP SOLANA ENVIRONMENT
     const network = process.env.ANCHOR_PROVIDER_URL || 'localnet';
     console.log(`\nRunning on ${G}${network}${U}`);
@@ -196,7 +226,7 @@ async function main() {
     // PROCESS COMMANDS
     if (cmd === Cmd.Mine) {
         console.log(`Running miner with params: address=${G}${address}${U}, priorityFee=${G}${priorityFee}${U}, runs=${G}${runs ? runs : 'auto'}${U}, delay=${G}${delay}${U}`);
-        console.log(`Using CU max=${G}${units}${U}`);
+        console.log(`Using CU max=${G}${units}${U}, iterations=${G}${iterations}${U}`);
         const associateTokenProgram = new web3.PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
         if (isMainThread) {
             for (const [currentKind] of Object.entries(contexts)) {
@@ -211,7 +241,8 @@ async function main() {
                         address,
                         delay,
                         priorityFee,
-                        units
+                        units,
+                        iterations
                     },
                 });
                 runner.on("message", console.log);
@@ -27,6 +27,10 @@ const G = '\x1b[32m';
 const Y = '\x1b[33m';
 const U = '\x1b[39m';
 
+// compute unit limit of a tx, and hashes per tx fitting in it (MAX_TX_UNITS and MAX_ITERATIONS in the SDK)
+const MAX_TX_UNITS = 1_400_000;
+const MAX_ITERATIONS = 86;
+
 export type PDAParams = {
     programId: any;
     kind: number;
@@ -43,6 +47,16 @@ export const getPDAs = ({programId, kind, address, wallet}: PDAParams) => {
         programId
     );
 
+    // same as `shard_index` in the miner program
+    const [xnShardAddress] = web3.PublicKey.findProgramAddressSync(
+        [
+            Buffer.from("xn-miner-shard"),
+            Buffer.from([kind]),
+            Buffer.from([wallet.publicKey.toBytes()[0] % 16]),
+        ],
+        programId
+    );
+
     const ethAddress20 = Buffer.from(address.slice(2), 'hex')
     const [userEthXnRecordAccount] = web3.PublicKey.findProgramAddressSync(
         [
@@ -66,6 +80,7 @@ export const getPDAs = ({programId, kind, address, wallet}: PDAParams) => {
 
     return {
         globalXnRecordAddress,
+        xnShardAddress,
         userEthXnRecordAccount,
         userSolXnRecordAccount,
         ethAddress20,
@@ -86,7 +101,8 @@ async function main() {
     let cmd: Cmd;
     let address: `0x${string}` = zeroAddress;
     let priorityFee: number = 1;
-    let units: number = 1_180_000;
+    let units: number;
+    let iterations: number = 72;
     let runs: number | null = null;
     let kind: number | null = null;
     let delay: number = 1;
@@ -104,8 +120,13 @@ async function main() {
         .option('units', {
             alias: 'u',
             type: 'number',
-            default: 1_400_000,
-            description: 'Solana MAX Compute Units'
+            description: 'Solana MAX Compute Units (derived from iterations by default)'
+        })
+        .option('iterations', {
+            alias: 'i',
+            type: 'number',
+            default: 72,
+            description: 'Hashes per tx, up to the kind\'s max_hashes and 86 (tx compute limit)'
         })
         .option('address', {
             alias: 'addr',
@@ -159,8 +180,19 @@ async function main() {
         priorityFee = Number(yArgs.priorityFee)
     }
 
-    if (yArgs.units) {
-        units = Number(yArgs.units)
+    if (yArgs.iterations) {
+        iterations = Number(yArgs.iterations)
+        if (iterations < 1 || iterations > MAX_ITERATIONS) {
+            console.log(`Wrong iterations, 1...${MAX_ITERATIONS}`)
+            process.exit(1)
+        }
+    }
+
+    // base cost of mine_hashes plus each hash iteration, see MINE_HASHES_*_UNITS in the SDK
+    units = yArgs.units ? Number(yArgs.units) : 100_000 + 15_000 * iterations
+    if (units > MAX_TX_UNITS) {
+        console.log(`Wrong units, up to ${MAX_TX_UNITS}`)
+        process.exit(1)
     }
 
     if (yArgs.runs) {
@@ -188,9 +220,10 @@ async function main() {
         }
     }
 
-    const minersStr = process.env.MINERS || 'B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN,2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ,5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ,DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S';
+    // all kinds are served by a single miner program
+    const minerStr = process.env.PROGRAM_ID_MINER || '9vCHCeZcC2yqgLwbECtNDzVEi3opBWdYeGKuJ19z14th';
 
-    const miners = minersStr.split(',').map(s => new web3.PublicKey(s));
+    const miners = [0, 1, 2, 3].map(() => new web3.PublicKey(minerStr));
 
     // SETUP SOLANA ENVIRONMENT
 
@@ -250,7 +283,7 @@ async function main() {
     if (cmd === Cmd.Mine) {
 
         console.log(`Running miner with params: address=${G}${address}${U}, priorityFee=${G}${priorityFee}${U}, runs=${G}${runs ? runs : 'auto'}${U}, delay=${G}${delay}${U}`);
-        console.log(`Using CU max=${G}${units}${U}`);
+        console.log(`Using CU max=${G}${units}${U}, iterations=${G}${iterations}${U}`);
 
         const associateTokenProgram = new web3.PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")
 
@@ -267,7 +300,8 @@ async function main() {
                         address,
                         delay,
                         priorityFee,
-                        units
+                        units,
+                        iterations
                     },
                 } as WorkerOptions);
                 runner.on("message", console.log);
@@ -11,7 +11,7 @@ const Y = '\x1b[33m';
 const U = '\x1b[39m';
 // parentPort?.postMessage({...workerData, threadId})
 let currentRun = 1;
-const { kind = 1, runs, address, delay = 0.5, priorityFee = 100000, units } = workerData || {};
+const { kind = 1, runs, address, delay = 0.5, priorityFee = 100000, units, iterations = 72 } = workerData || {};
 const i = kind;
 const network = process.env.ANCHOR_PROVIDER_URL || 'localnet';
 const connection = new web3.Connection(network, 'processed');
@@ -50,7 +50,7 @@ setInterval(() => {
         process.exit(0);
     }
     const programId = program.programId; //  miners[kind || currentKind];)
-    const { globalXnRecordAddress, userEthXnRecordAccount, userSolXnRecordAccount, ethAddress20 } = getPDAs({
+    const { globalXnRecordAddress, xnShardAddress, userEthXnRecordAccount, userSolXnRecordAccount, ethAddress20 } = getPDAs({
         programId,
         kind,
         address,
@@ -62,13 +62,12 @@ setInterval(() => {
         xnByEth: userEthXnRecordAccount,
         xnBySol: userSolXnRecordAccount,
         globalXnRecord: globalXnRecordAddress,
+        xnShard: xnShardAddress,
         programId
     };
-    const ethAddr = {
-        address: Array.from(ethAddress20),
-        addressStr: address
-    };
-    program.methods.mineHashes(ethAddr, kind)
+    // the address was parsed and checksummed by the multiminer, mine_hashes_v2 takes the raw bytes only
+    const ethAddr = Array.from(ethAddress20);
+    program.methods.mineHashesV2(ethAddr, kind, iterations)
         .accounts(mintAccounts)
         .signers([wallet.payer])
         .preInstructions([modifyComputeUnits, addPriorityFee])
@@ -22,6 +22,7 @@ export type RunnerParams = {
     address: `0x${string}`;
     delay: number;
     units: number;
+    iterations: number;
     priorityFee: number;
 }
 
@@ -31,7 +32,7 @@ const U = '\x1b[39m';
 
 // parentPort?.postMessage({...workerData, threadId})
 let currentRun = 1;
-const { kind = 1, runs, address, delay = 0.5, priorityFee = 100000, units } = workerData as RunnerParams || {};
+const { kind = 1, runs, address, delay = 0.5, priorityFee = 100000, units, iterations = 72 } = workerData as RunnerParams || {};
 
 const i = kind;
 const network = process.env.ANCHOR_PROVIDER_URL || 'localnet';
@@ -78,6 +79,7 @@ setInterval(() => {
 
     const {
         globalXnRecordAddress,
+        xnShardAddress,
         userEthXnRecordAccount,
         userSolXnRecordAccount,
         ethAddress20
@@ -94,14 +96,13 @@ setInterval(() => {
         xnByEth: userEthXnRecordAccount,
         xnBySol: userSolXnRecordAccount,
         globalXnRecord: globalXnRecordAddress,
+        xnShard: xnShardAddress,
         programId
     };
-    const ethAddr = {
-        address: Array.from(ethAddress20),
-        addressStr: address
-    };
+    // the address was parsed and checksummed by the multiminer, mine_hashes_v2 takes the raw bytes only
+    const ethAddr = Array.from(ethAddress20);
 
-    program.methods.mineHashes(ethAddr, kind)
+    program.methods.mineHashesV2(ethAddr, kind, iterations)
         .accounts(mintAccounts)
         .signers([wallet.payer])
         .preInstructions([modifyComputeUnits, addPriorityFee])
@@ -20,7 +20,6 @@ solana config set --url "$url"
 # solana airdrop 100
 
 max=4
-miners=''
 
 current_slot=$(solana slot |   awk  '{print $0}')
 echo "current slot= $current_slot"
@@ -31,77 +30,70 @@ echo "start slot= $start_slot"
 
 timestamp=$(date +%s)
 
-for (( kind=0; kind < $max; kind++ ))
-do
-    echo
-    echo
-    echo "##### Miner kind=$kind #####"
-    echo
+echo
+echo
+echo "##### Miner #####"
+echo
 
-    rm ./target/deploy/sol_xen_miner.so
-    rm ./target/deploy/sol_xen_miner-keypair.json
+rm ./target/deploy/sol_xen_miner.so
+rm ./target/deploy/sol_xen_miner-keypair.json
+rm ./target/deploy/sol_xen_minter-keypair.json
 
-    anchor build -p sol-xen-miner
-    key=$(anchor keys list | grep "sol_xen_miner" | awk -F': ' '{print $2}')
+# the miner checks minted points against the minter when closing user records, so the minter key comes first
+solana-keygen new --no-bip39-passphrase -s -o ./target/deploy/sol_xen_minter-keypair.json
+minter_key=$(solana-keygen pubkey ./target/deploy/sol_xen_minter-keypair.json)
 
-    echo "   key= $key"
-    gsed -i 's/declare_id!("\(.*\)");/declare_id!("'$key'");/' ./programs/sol-xen-miner/src/lib.rs
-    # const START_SLOT: u64 = 301_816_336;
-    gsed -i 's/const START_SLOT: u64 = \(.*\);/const START_SLOT: u64 = '$start_slot';/' ./programs/sol-xen-miner/src/lib.rs
+anchor build -p sol-xen-miner
+miner_key=$(anchor keys list | grep "sol_xen_miner" | awk -F': ' '{print $2}')
 
-    anchor build -p sol-xen-miner
+echo "   miner key= $miner_key"
+gsed -i 's/declare_id!("\(.*\)");/declare_id!("'$miner_key'");/' ./programs/sol-xen-miner/src/lib.rs
+gsed -i 's/pub const MINTER: Pubkey = solana_program::pubkey!("\(.*\)");/pub const MINTER: Pubkey = solana_program::pubkey!("'$minter_key'");/' ./programs/sol-xen-miner/src/lib.rs
 
-    echo
-    echo
-    anchor deploy -p sol-xen-miner -- --with-compute-unit-price 1000000  --max-sign-attempts 1000
+anchor build -p sol-xen-miner
 
-    echo
-    echo "Sleeping for 5s..."
-    sleep 5
+echo
+echo
+anchor deploy -p sol-xen-miner -- --with-compute-unit-price 1000000  --max-sign-attempts 1000
 
+echo
+echo "Sleeping for 5s..."
+sleep 5
+
+echo
+echo "Initializing miner config..."
+echo
+tsx ./admin/init_miner_config.ts
+
+# one program serves all kinds, each with its own global record
+for (( kind=0; kind < $max; kind++ ))
+do
     echo
-    echo "Initializing miner..."
+    echo "Initializing miner kind=$kind..."
     echo
 
-    tsx ./admin/init_miner.ts --kind $kind
+    tsx ./admin/init_miner.ts --kind $kind --startSlot $start_slot
 
     # should fail!
     # tsx ./admin/init_miner.ts --kind $kind
-
-    solana program set-upgrade-authority "$key" -u "$url" --final
-
-    miners="$miners$key"
-    max_less_1=$((max - 1))
-    if [ "$kind" -lt "$max_less_1" ]; then
-      miners="$miners,"
-    fi
-
-    # "address": "5i4ZPZujwASXGSYENhQEEijiU4EWBzobPAKzKUs87khw",
-    gsed -i 's/"address": "\(.*\)",/"address": "'$key'",/' "./target/idl/sol_xen_miner_$kind.json"
-    gsed -i 's/"address": "\(.*\)",/"address": "'$key'",/' "./target/types/sol_xen_miner_$kind.ts"
-
-    echo
 done
 
+solana program set-upgrade-authority "$miner_key" -u "$url" --final
+
 echo
-echo "Miners=$miners"
+echo "Miner=$miner_key"
 
 echo
 echo
 echo "##### Minter #####"
 echo
 
-gsed -i 's/comma_delimited = "\(.*\)";/comma_delimited = "'$miners'";/' ./programs/sol-xen-minter/src/lib.rs
-
 rm ./target/deploy/sol_xen_minter.so
-rm ./target/deploy/sol_xen_minter-keypair.json
-
-anchor build -p sol-xen-miner
-minter_key=$(anchor keys list | grep "sol_xen_minter" | awk -F': ' '{print $2}')
 
 echo "   minter key= $minter_key"
 gsed -i 's/declare_id!("\(.*\)");/declare_id!("'$minter_key'");/' ./programs/sol-xen-minter/src/lib.rs
 gsed -i 's/const START_SLOT: u64 = \(.*\);/const START_SLOT: u64 = '$start_slot';/' ./programs/sol-xen-minter/src/lib.rs
+# the minter config registers sol_xen_miner::ID, picked up from the miner crate
 
 anchor build -p sol-xen-minter
 
@@ -113,6 +105,12 @@ echo
 echo "Sleeping for 5s..."
 sleep 5
 
+echo
+echo "Initializing minter config..."
+echo
+# registers the miners of sol_xen_minter::MINERS; more can be added later by the admin (admin/minter_registry.ts)
+tsx ./admin/init_minter_config.ts
+
 echo
 echo "Initializing minter..."
 echo
@@ -122,6 +120,12 @@ tsx ./admin/init_minter.ts
 # should fail
 # tsx ./admin/init_minter.ts
 
+echo
+echo "Initializing minter supply..."
+echo
+# no cap; the admin can set one later with `tsx ./admin/minter_supply.ts cap --cap <base units>`
+tsx ./admin/minter_supply.ts init
+
 solana program set-upgrade-authority "$minter_key" -u "$url" --final
 
 timestamp_end=$(date +%s)
@@ -142,13 +146,12 @@ echo
 echo
 echo "Modifying clients"
 echo
-# || '....';
-gsed -i "s/process.env.MINERS || '\(.*\)';/process.env.MINERS || '"$miners"';/" ./client/multiminer.ts
+# '9vCH...';
+gsed -i "s/process.env.PROGRAM_ID_MINER || '\(.*\)';/process.env.PROGRAM_ID_MINER || '"$miner_key"';/" ./client/miner.ts ./client/minter.ts ./client/multiminer.ts
 
-# const MINERS: &str = "
-gsed -i 's/const MINERS: \&str = "\(.*\)";/const MINERS: \&str = "'$miners'";/' ./app/sol-xen-client/src/main.rs
-gsed -i 's/const MINERS: \&str = "\(.*\)";/const MINERS: \&str = "'$miners'";/' ./app/sol-xen-multiminer/src/main.rs
-gsed -i 's/const MINTER: \&str = "\(.*\)";/const MINTER: \&str = "'$minter_key'";/' ./app/sol-xen-multiminer/src/main.rs
+# pub const MINER: Pubkey = pubkey!("
+gsed -i 's/pub const MINER: Pubkey = pubkey!("\(.*\)");/pub const MINER: Pubkey = pubkey!("'$miner_key'");/' ./app/sol-xen-sdk/src/lib.rs
+gsed -i 's/pub const MINTER: Pubkey = pubkey!("\(.*\)");/pub const MINTER: Pubkey = pubkey!("'$minter_key'");/' ./app/sol-xen-sdk/src/lib.rs
 
 # echo
 # echo "Doing test mines and mints"
@@ -22,62 +22,48 @@
 
 # solana airdrop 100
 
-max=4
-miners=''
-
-current_slot=$(solana slot |   awk  '{print $0}')
-echo "current slot= $current_slot"
-
-# start_slot=$((current_slot+330))
-start_slot=1
-echo "start slot= $start_slot"
-
 timestamp=$(date +%s)
 
-miners="B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN,2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ,5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ,DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S"
+# the unified miner, deployed beforehand (README, migration step 1); the minter registers sol_xen_miner::ID for all kinds
+miner_key=$(solana-keygen pubkey ./target/deploy/sol_xen_miner-keypair.json)
+# the minter is upgraded in place: same program id, mint, config and user records
+minter_key=$(grep 'declare_id!' ./programs/sol-xen-minter/src/lib.rs | gsed 's/declare_id!("\(.*\)");/\1/')
 
-echo
-echo "Miners=$miners"
+echo "   miner key= $miner_key"
+echo "   minter key= $minter_key"
 
 echo
 echo
 echo "##### Minter #####"
 echo
 
-gsed -i 's/comma_delimited = "\(.*\)";/comma_delimited = "'$miners'";/' ./programs/sol-xen-minter/src/lib.rs
+gsed -i 's/declare_id!("\(.*\)");/declare_id!("'$miner_key'");/' ./programs/sol-xen-miner/src/lib.rs
 
 rm ./target/deploy/sol_xen_minter.so
-rm ./target/deploy/sol_xen_minter-keypair.json
-
-anchor build -p sol-xen-minter
-minter_key=$(anchor keys list | grep "sol_xen_minter" | awk -F': ' '{print $2}')
-
-echo "   minter key= $minter_key"
-gsed -i 's/declare_id!("\(.*\)");/declare_id!("'$minter_key'");/' ./programs/sol-xen-minter/src/lib.rs
-gsed -i 's/const START_SLOT: u64 = \(.*\);/const START_SLOT: u64 = '$start_slot';/' ./programs/sol-xen-minter/src/lib.rs
 
 anchor build -p sol-xen-minter
 
 echo
 echo
-anchor deploy -p sol-xen-minter -- --with-compute-unit-price 1000000 ----max-sign-attempts 1000 --use-rpc --buffer /Users/lbelyaev/.config/solana/id.json
-# solana program deploy ./target/deploy/sol_xen_minter.so --with-compute-unit-price 1000000 ----max-sign-attempts 1000 --use-rpc
-# anchor deploy -p sol-xen-minter
+anchor upgrade ./target/deploy/sol_xen_minter.so --program-id "$minter_key" -- --with-compute-unit-price 1000000 --max-sign-attempts 1000 --use-rpc
 
 echo
 echo "Sleeping for 5s..."
 sleep 5
 
 echo
-echo "Initializing minter..."
+echo "Initializing minter config..."
 echo
+# registers the unified miner for every kind (sol_xen_minter::MINERS); the legacy ones are refused
+tsx ./admin/init_minter_config.ts
 
-tsx ./admin/init_minter.ts
-
-# should fail
-# tsx ./admin/init_minter.ts
+echo
+echo "Initializing minter supply..."
+echo
+# no cap; the admin can set one later with `tsx ./admin/minter_supply.ts cap --cap <base units>`
+tsx ./admin/minter_supply.ts init
 
-# solana program set-upgrade-authority "$minter_key" -u "$url" --final
+# not --final: the minter stays upgradeable
 
 timestamp_end=$(date +%s)
 
@@ -85,11 +71,6 @@ delta=$((timestamp_end-timestamp))
 
 echo "Deployment took $delta s"
 
-# echo
-# echo "Relinquishing control..."
-# echo
-# tsx ./admin/relinquish_mint_control.ts
-
 echo
 echo "Deployment done"
 echo
@@ -97,20 +78,11 @@ echo
 echo
 echo "Modifying clients"
 echo
-# || '....';
-# gsed -i "s/process.env.MINERS || '\(.*\)';/process.env.MINERS || '"$miners"';/" ./client/multiminer.ts
-
-# const MINERS: &str = "
-# gsed -i 's/const MINERS: \&str = "\(.*\)";/const MINERS: \&str = "'$miners'";/' ./app/sol-xen-client/src/main.rs
-# gsed -i 's/const MINERS: \&str = "\(.*\)";/const MINERS: \&str = "'$miners'";/' ./app/sol-xen-multiminer/src/main.rs
-gsed -i 's/const MINTER: \&str = "\(.*\)";/const MINTER: \&str = "'$minter_key'";/' ./app/sol-xen-client/src/main.rs
-gsed -i 's/const MINTER: \&str = "\(.*\)";/const MINTER: \&str = "'$minter_key'";/' ./app/sol-xen-multiminer/src/main.rs
+# '9vCH...';
+gsed -i "s/process.env.PROGRAM_ID_MINER || '\(.*\)';/process.env.PROGRAM_ID_MINER || '"$miner_key"';/" ./client/miner.ts ./client/minter.ts ./client/multiminer.ts
 
-# echo
-# echo "Doing test mines and mints"
-# echo "Running TSX multiminer"
-# echo
-# tsx ./client/multiminer.ts mine --address 0x6B889Dcfad1a6ddf7dE3bC9417F5F51128efc964 -r 10 -f 1 -d 1 -a 10
+# pub const MINER: Pubkey = pubkey!("
+gsed -i 's/pub const MINER: Pubkey = pubkey!("\(.*\)");/pub const MINER: Pubkey = pubkey!("'$miner_key'");/' ./app/sol-xen-sdk/src/lib.rs
 
 echo
 # echo "Pausing for 5s"
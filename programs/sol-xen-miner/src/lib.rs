@@ -1,121 +1,404 @@
 use anchor_lang::{
     prelude::*,
+    Discriminator,
 };
 use sha3::{Digest, Keccak256};
 use ethaddr::Address;
 
-declare_id!("B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN");
+declare_id!("9vCHCeZcC2yqgLwbECtNDzVEi3opBWdYeGKuJ19z14th");
 
-const MAX_HASHES: u8 = 72;
-const HASH_PATTERN: &str = "420";
-const SUPERHASH_PATTERN: &str = "42069";
-const SUPERHASH_X: u16 = 250;
-const AMP_START: u16 = 300;
-const AMP_CYCLE_SLOTS: u64 = 100_000;
+/// Number of miner kinds, each one with its own `GlobalXnRecord`
+pub const MAX_KINDS: u8 = 4;
 
-const START_SLOT: u64 = 268484759;
+/// Number of `GlobalXnShard`s per kind `mine_hashes` spreads its writes over, see `shard_index`
+pub const SHARDS: u8 = 16;
 
-// TODO: lock to a specifig admin key
-// const ADMIN_KEY: &str = "somesecretadminkey";
+/// Per-kind copies of this program deployed before all kinds were served by a single one; they are
+/// immutable and keep mining, points mined with them are imported with `import_legacy_record`
+/// (again whenever there are new ones) before the minter can convert them
+pub const LEGACY_MINERS: [Pubkey; MAX_KINDS as usize] = [
+    solana_program::pubkey!("B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN"),
+    solana_program::pubkey!("2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ"),
+    solana_program::pubkey!("5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ"),
+    solana_program::pubkey!("DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S"),
+];
+
+/// `sol_xen_minter` program: user records can only be closed once their points have been minted there
+pub const MINTER: Pubkey = solana_program::pubkey!("EPAdVJ5S317jJr2ejgxoA52iptvphGXjPLbqXhZH4n8o");
+
+// bounds for the admin-configurable mining params, enforced by `MinerParamsArgs::validate`
+pub const MAX_HASHES_LIMIT: u8 = 128;
+const MAX_PATTERN_LEN: usize = 16;
+pub const MAX_SUPERHASH_X: u16 = 1_000;
+pub const MAX_AMP_START: u16 = 1_000;
+
+/// Layout version of `MinerParams`: 2 added `mix_slot_hash`. Version 1 params decode it as `false`
+/// from the zeroed tail of the account (pattern strings never fill their `max_len`)
+const MINER_PARAMS_VERSION: u8 = 2;
+
+const POINTS_DECIMALS: u128 = 1_000_000_000;
+
+/// Layout version of `GlobalXnRecord`, `UserEthXnRecord` and `UserSolXnRecord`, stored in their first byte.
+/// Older layouts had no version byte and are told apart by size:
+/// 0 - original one with `superhashes: u32`, 1 - `superhashes: u64`
+pub const RECORD_VERSION: u8 = 2;
+
+/// Layout version of `GlobalXnShard`; 0 until `mine_hashes` sets the shard up
+pub const SHARD_VERSION: u8 = 1;
+
+/// `msg!` in builds with the `verbose-logs` feature, nothing otherwise: the mining path only emits its events by default
+macro_rules! verbose_msg {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "verbose-logs")]
+        msg!($($arg)*);
+    };
+}
 
 #[program]
 pub mod sol_xen_miner {
     use super::*;
 
-    pub fn init_miner(ctx: Context<InitMiner>, kind: u8) -> Result<()> {
+    pub fn init_config(ctx: Context<InitConfig>) -> Result<()> {
+        // program upgrade authority becomes the first admin
+        ctx.accounts.config.admin = ctx.accounts.admin.key();
+        ctx.accounts.config.pending_admin = Pubkey::default();
+        Ok(())
+    }
+
+    pub fn transfer_admin(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<()> {
+        // two-step handover: new admin has to accept to take over
+        ctx.accounts.config.pending_admin = new_admin;
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        ctx.accounts.config.admin = ctx.accounts.pending_admin.key();
+        ctx.accounts.config.pending_admin = Pubkey::default();
+        Ok(())
+    }
+
+    pub fn init_miner(ctx: Context<InitMiner>, kind: u8, params: MinerParamsArgs) -> Result<()> {
 
         msg!("Global last slot check: {}", ctx.accounts.global_xn_record.last_amp_slot);
         require!(ctx.accounts.global_xn_record.last_amp_slot == 0, SolXenError::MintIsAlreadyActive);
-        require!(kind < 4, SolXenError::InvalidMinerKind);
+        require!(kind < MAX_KINDS, SolXenError::InvalidMinerKind);
+        params.validate()?;
+
+        let slot = Clock::get().unwrap().slot;
+
+        // seed the nonce so that each kind starts from a different one
+        let mut hasher = Keccak256::new();
+        hasher.update(ctx.accounts.admin.key.to_bytes());
+        hasher.update(kind.to_le_bytes());
+        hasher.update(slot.to_le_bytes());
+        let nonce = hasher.finalize();
 
         // initialize global state
+        ctx.accounts.global_xn_record.version = RECORD_VERSION;
         ctx.accounts.global_xn_record.kind = kind;
-        ctx.accounts.global_xn_record.amp = AMP_START;
-        ctx.accounts.global_xn_record.last_amp_slot = Clock::get().unwrap().slot;
-        ctx.accounts.global_xn_record.nonce = ctx.accounts.admin.key.to_bytes()[0..4].try_into().unwrap();
+        ctx.accounts.global_xn_record.amp = params.amp_start;
+        ctx.accounts.global_xn_record.last_amp_slot = slot;
+        ctx.accounts.global_xn_record.nonce = nonce[0..4].try_into().unwrap();
+
+        ctx.accounts.params.version = MINER_PARAMS_VERSION;
+        ctx.accounts.params.kind = kind;
+        ctx.accounts.params.set(params.clone());
+
+        emit!(MinerInitialized {
+            kind,
+            admin: ctx.accounts.admin.key(),
+            slot,
+            nonce: ctx.accounts.global_xn_record.nonce,
+            params,
+        });
 
         Ok(())
     }
 
-    pub fn mine_hashes(ctx: Context<MineHashes>, eth_account: EthAccount, _kind: u8) -> Result<()> {
+    /// A new `amp_cycle_slots` (or `end_slot`) only applies from the last cycle boundary on: the decay due under
+    /// the old schedule is stored first, as `crank_totals` does
+    pub fn update_params(ctx: Context<UpdateParams>, kind: u8, params: MinerParamsArgs) -> Result<()> {
+        require!(kind < MAX_KINDS, SolXenError::InvalidMinerKind);
+        params.validate()?;
+
+        let slot = Clock::get().unwrap().slot;
+        let global = &mut ctx.accounts.global_xn_record;
+        let previous_amp_slot = global.last_amp_slot;
+        if global.store_decay(slot, &ctx.accounts.params) {
+            emit!(AmpDecayed {
+                kind,
+                slot,
+                amp: global.amp,
+                previous_amp_slot,
+            });
+        }
+
+        let old = ctx.accounts.params.args();
+        ctx.accounts.params.version = MINER_PARAMS_VERSION;
+        ctx.accounts.params.set(params.clone());
+
+        emit!(ParamsUpdated {
+            kind,
+            admin: ctx.accounts.admin.key(),
+            old,
+            new: params
+        });
+
+        Ok(())
+    }
+
+    /// `iterations` (up to the kind's `max_hashes`) is the number of hashes to compute; compute units
+    /// and expected points both grow linearly with it. `address_str` is `eth_address` checksummed, validated here
+    pub fn mine_hashes(
+        ctx: Context<MineHashes>,
+        eth_address: [u8; 20],
+        kind: u8,
+        iterations: u8,
+        address_str: String,
+    ) -> Result<()> {
+        require!(kind < MAX_KINDS, SolXenError::InvalidMinerKind);
 
         // recover check-summed address from string and validate it
-        let maybe_eth_address = Address::from_str_checksum(&eth_account.address_str);
+        let maybe_eth_address = Address::from_str_checksum(&address_str);
         require!(maybe_eth_address.is_ok(), SolXenError::InvalidEthAddressChecksum);
-        require!(maybe_eth_address.unwrap().as_slice()[0..20] == eth_account.address.as_slice()[0..20], SolXenError::InvalidEthAddressData);
+        require!(maybe_eth_address.unwrap().as_slice()[0..20] == eth_address.as_slice()[0..20], SolXenError::InvalidEthAddressData);
 
-        // Get the current slot number
-        let slot = Clock::get().unwrap().slot;
-        require!(slot > START_SLOT, SolXenError::MintIsNotActive);
+        mine(ctx.accounts, eth_address, kind, iterations)
+    }
 
-        print!("Using slot #{}", slot);
+    /// Same as `mine_hashes`, with the raw Ethereum address only: its checksum is left to the client,
+    /// which makes the tx smaller and cheaper in compute
+    pub fn mine_hashes_v2(ctx: Context<MineHashes>, eth_address: [u8; 20], kind: u8, iterations: u8) -> Result<()> {
+        require!(kind < MAX_KINDS, SolXenError::InvalidMinerKind);
 
-        require!(slot > 0, SolXenError::ZeroSlotValue);
+        mine(ctx.accounts, eth_address, kind, iterations)
+    }
 
-        // update global AMP state if required
-        if slot > ctx.accounts.global_xn_record.last_amp_slot
-            && slot - ctx.accounts.global_xn_record.last_amp_slot > AMP_CYCLE_SLOTS
-            && ctx.accounts.global_xn_record.amp > 0
-        {
-            ctx.accounts.global_xn_record.amp -= 1;
-            ctx.accounts.global_xn_record.last_amp_slot = slot;
-        }
+    /// Permissionless: adds what was mined on the shards passed as remaining accounts since their last crank to the
+    /// kind's totals in `GlobalXnRecord`, and stores the AMP decay due. Shards not set up yet are skipped
+    pub fn crank_totals(ctx: Context<CrankTotals>, kind: u8) -> Result<()> {
+        let slot = Clock::get().unwrap().slot;
+        let params = &ctx.accounts.params;
+        let global = &mut ctx.accounts.global_xn_record;
 
-        // Find hashes
-        let nonce = ctx.accounts.global_xn_record.nonce;
-        let (hashes, superhashes) = find_hashes(slot, nonce);
+        let previous_amp_slot = global.last_amp_slot;
+        if global.store_decay(slot, params) {
+            emit!(AmpDecayed {
+                kind,
+                slot,
+                amp: global.amp,
+                previous_amp_slot,
+            });
+        }
 
-        // Calculate points convertible to solXEN tokens
-        let points = 1_000_000_000 * (ctx.accounts.global_xn_record.amp as u64) * (hashes as u64)
-            + 1_000_000_000 * (ctx.accounts.global_xn_record.amp as u64) * (SUPERHASH_X as u64) * (superhashes as u64);
-        print!("Mined hashes {} superhashes {} points {} nonce {:?}", hashes, superhashes, points, nonce);
+        let (mut shards, mut hashes, mut superhashes, mut points) = (0u8, 0u64, 0u64, 0u128);
+        for account in ctx.remaining_accounts {
+            if account.data_is_empty() {
+                continue;
+            }
+            require_keys_eq!(*account.owner, ID, SolXenError::InvalidRecord);
+            let mut shard = GlobalXnShard::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+            require!(shard.kind == kind, SolXenError::InvalidRecord);
 
-        // Update user scores by eth address
-        ctx.accounts.xn_by_eth.hashes += hashes as u64;
-        ctx.accounts.xn_by_eth.superhashes += superhashes as u32;
+            hashes = hashes.checked_add(shard.hashes).ok_or(SolXenError::PointsOverflow)?;
+            superhashes = superhashes.checked_add(shard.superhashes).ok_or(SolXenError::PointsOverflow)?;
+            points = points.checked_add(shard.points).ok_or(SolXenError::PointsOverflow)?;
+            shards += 1;
 
-        // Update user scores by sol address
-        ctx.accounts.xn_by_sol.hashes += hashes as u64;
-        ctx.accounts.xn_by_sol.superhashes += superhashes as u32;
-        ctx.accounts.xn_by_sol.points += points as u128;
+            // written back right away, so a shard passed twice is only counted once
+            shard.hashes = 0;
+            shard.superhashes = 0;
+            shard.points = 0;
+            shard.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+        }
 
-        // Update miner's scores accumulators
-        ctx.accounts.global_xn_record.hashes += hashes as u64;
-        ctx.accounts.global_xn_record.superhashes += superhashes as u32;
-        ctx.accounts.global_xn_record.points += points as u128;
+        global.hashes = global.hashes.checked_add(hashes).ok_or(SolXenError::PointsOverflow)?;
+        global.superhashes = global.superhashes.checked_add(superhashes).ok_or(SolXenError::PointsOverflow)?;
+        global.points = global.points.checked_add(points).ok_or(SolXenError::PointsOverflow)?;
 
-        // calculate and store new nonce
-        let mut hasher = Keccak256::new();
-        hasher.update(ctx.accounts.user.key.to_bytes());
-        hasher.update(hashes.to_le_bytes());
-        hasher.update(superhashes.to_le_bytes());
-        hasher.update(slot.to_le_bytes());
-        let nonce = hasher.finalize();
-        ctx.accounts.global_xn_record.nonce = nonce[0..4].try_into().unwrap();
-
-        // Emit hash tx record
-        emit!(HashEvent {
+        emit!(TotalsCranked {
+            kind,
             slot,
-            user: *ctx.accounts.user.key,
-            eth_account: eth_account.address,
+            shards,
             hashes,
             superhashes,
-            points
+            points,
+            global_hashes: global.hashes,
+            global_superhashes: global.superhashes,
+            global_points: global.points,
         });
+        Ok(())
+    }
 
+    pub fn import_legacy_record(ctx: Context<ImportLegacyRecord>, kind: u8) -> Result<()> {
+        require!(kind < MAX_KINDS, SolXenError::InvalidMinerKind);
+        let legacy_program = ctx.accounts.legacy_program.key();
+        require_keys_eq!(legacy_program, LEGACY_MINERS[kind as usize], SolXenError::InvalidMinerKind);
+        require_keys_eq!(*ctx.accounts.legacy_record.owner, legacy_program, SolXenError::InvalidRecord);
+        // a closed record would restart from the imported points only, below what has been minted
+        let slot = Clock::get().unwrap().slot;
+        require!(
+            ctx.accounts.global_xn_record.remaining_cycles(slot, &ctx.accounts.params) > 0,
+            SolXenError::MiningIsOver
+        );
+
+        let legacy = read_record::<UserSolXnRecord>(&ctx.accounts.legacy_record.try_borrow_data()?)
+            .ok_or(SolXenError::InvalidRecord)?;
+        // the legacy miner keeps mining: only what it added since the last import is new
+        let legacy_import = &mut ctx.accounts.legacy_import;
+        let hashes = legacy.hashes.checked_sub(legacy_import.hashes).ok_or(SolXenError::InvalidRecord)?;
+        let superhashes = legacy.superhashes.checked_sub(legacy_import.superhashes).ok_or(SolXenError::InvalidRecord)?;
+        let points = legacy.points.checked_sub(legacy_import.points).ok_or(SolXenError::InvalidRecord)?;
+        msg!("Importing legacy hashes {} superhashes {} points {}", hashes, superhashes, points);
+
+        let xn_by_sol = &mut ctx.accounts.xn_by_sol;
+        xn_by_sol.version = RECORD_VERSION;
+        xn_by_sol.hashes = xn_by_sol.hashes.checked_add(hashes).ok_or(SolXenError::PointsOverflow)?;
+        xn_by_sol.superhashes = xn_by_sol.superhashes.checked_add(superhashes).ok_or(SolXenError::PointsOverflow)?;
+        xn_by_sol.points = xn_by_sol.points.checked_add(points).ok_or(SolXenError::PointsOverflow)?;
+
+        legacy_import.legacy_program = legacy_program;
+        legacy_import.hashes = legacy.hashes;
+        legacy_import.superhashes = legacy.superhashes;
+        legacy_import.points = legacy.points;
         Ok(())
     }
+
+    pub fn close_user_record(ctx: Context<CloseUserRecord>, kind: u8) -> Result<()> {
+        require!(kind < MAX_KINDS, SolXenError::InvalidMinerKind);
+        // once closed, a record mined again would restart from zero points, below what has been minted
+        let slot = Clock::get().unwrap().slot;
+        require!(
+            ctx.accounts.global_xn_record.remaining_cycles(slot, &ctx.accounts.params) == 0,
+            SolXenError::MiningIsNotOver
+        );
+
+        let xn_by_sol = &ctx.accounts.xn_by_sol;
+        let minted = minted_points(&ctx.accounts.user_tokens_record, kind)?;
+        msg!("Closing record with points {}, minted {}", xn_by_sol.points, minted);
+        require!(minted >= xn_by_sol.points, SolXenError::PointsNotMinted);
+
+        // the minter keeps the user's points total of `kind`, the event keeps the rest of the record
+        emit!(UserRecordClosed {
+            kind,
+            user: ctx.accounts.user.key(),
+            hashes: xn_by_sol.hashes,
+            superhashes: xn_by_sol.superhashes,
+            points: xn_by_sol.points,
+            lamports: xn_by_sol.to_account_info().lamports(),
+        });
+        Ok(())
+    }
+
+    pub fn get_mining_status(ctx: Context<GetMiningStatus>, kind: u8) -> Result<MiningStatus> {
+        let slot = Clock::get().unwrap().slot;
+        let global = &ctx.accounts.global_xn_record;
+        let remaining_cycles = global.remaining_cycles(slot, &ctx.accounts.params);
+
+        Ok(MiningStatus {
+            kind,
+            slot,
+            amp: global.amp_at(slot, &ctx.accounts.params),
+            end_slot: ctx.accounts.params.end_slot,
+            remaining_cycles,
+            is_over: remaining_cycles == 0,
+        })
+    }
 }
 
-// TODO 1: add checks to lock this method to a specific (admin) Key
-// TODO 2: after the Token Mint is launched, remove authority from it (First Principles)
-// DONE 3: add metadata support (https://github.com/solana-developers/program-examples/blob/main/tokens/pda-mint-authority/anchor/programs/token-minter/src/instructions/create.rs)
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        space = 8 + MinerConfig::INIT_SPACE,
+        seeds = [b"xn-miner-config"],
+        bump,
+        payer = admin,
+    )]
+    pub config: Box<Account<'info, MinerConfig>>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, program::SolXenMiner>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ SolXenError::NotAdmin)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAdmin<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"xn-miner-config"],
+        bump,
+        has_one = admin @ SolXenError::NotAdmin,
+    )]
+    pub config: Box<Account<'info, MinerConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"xn-miner-config"],
+        bump,
+        has_one = pending_admin @ SolXenError::NotPendingAdmin,
+    )]
+    pub config: Box<Account<'info, MinerConfig>>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: u8)]
+pub struct GetMiningStatus<'info> {
+    #[account(
+        seeds = [b"xn-miner-global", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub global_xn_record: Box<Account<'info, GlobalXnRecord>>,
+    #[account(
+        seeds = [b"xn-miner-params", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub params: Box<Account<'info, MinerParams>>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: u8)]
+pub struct UpdateParams<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"xn-miner-config"],
+        bump,
+        has_one = admin @ SolXenError::NotAdmin,
+    )]
+    pub config: Box<Account<'info, MinerConfig>>,
+    #[account(
+        mut,
+        seeds = [b"xn-miner-params", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub params: Box<Account<'info, MinerParams>>,
+    #[account(
+        mut,
+        seeds = [b"xn-miner-global", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub global_xn_record: Box<Account<'info, GlobalXnRecord>>,
+}
 
 #[derive(Accounts)]
 #[instruction(kind: u8)]
 pub struct InitMiner<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"xn-miner-config"],
+        bump,
+        has_one = admin @ SolXenError::NotAdmin,
+    )]
+    pub config: Box<Account<'info, MinerConfig>>,
     #[account(
         init_if_needed,
         space = 8 + GlobalXnRecord::INIT_SPACE,
@@ -124,26 +407,51 @@ pub struct InitMiner<'info> {
         payer = admin,
     )]
     pub global_xn_record: Box<Account<'info, GlobalXnRecord>>,
+    #[account(
+        init_if_needed,
+        space = 8 + MinerParams::INIT_SPACE,
+        seeds = [b"xn-miner-params", kind.to_be_bytes().as_slice()],
+        bump,
+        payer = admin,
+    )]
+    pub params: Box<Account<'info, MinerParams>>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+/// Accounts of `mine_hashes` and `mine_hashes_v2`, both taking the address bytes and the kind first
 #[derive(Accounts)]
-#[instruction(eth_account: EthAccount, kind: u8)]
+#[instruction(eth_address: [u8; 20], kind: u8)]
 pub struct MineHashes<'info> {
     #[account(
-        mut,
         seeds = [b"xn-miner-global", kind.to_be_bytes().as_slice()],
         bump,
     )]
     pub global_xn_record: Box<Account<'info, GlobalXnRecord>>,
+    #[account(
+        init_if_needed,
+        space = 8 + GlobalXnShard::INIT_SPACE,
+        payer = user,
+        seeds = [
+            b"xn-miner-shard",
+            kind.to_be_bytes().as_slice(),
+            shard_index(&user.key()).to_be_bytes().as_slice(),
+        ],
+        bump
+    )]
+    pub xn_shard: Box<Account<'info, GlobalXnShard>>,
+    #[account(
+        seeds = [b"xn-miner-params", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub params: Box<Account<'info, MinerParams>>,
     #[account(
         init_if_needed,
         space = 8 + UserEthXnRecord::INIT_SPACE,
         payer = user,
         seeds = [
             b"xn-by-eth",
-            eth_account.address.as_ref(),
+            eth_address.as_ref(),
             kind.to_be_bytes().as_slice(),
             ID.as_ref(),
         ],
@@ -167,66 +475,607 @@ pub struct MineHashes<'info> {
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
     // pub rent: Sysvar<'info, Rent>,
+    /// CHECK: too large to deserialize, read by `recent_slot_hash` when `mix_slot_hash` is set
+    #[account(address = solana_program::sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: u8)]
+pub struct ImportLegacyRecord<'info> {
+    #[account(
+        seeds = [b"xn-miner-global", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub global_xn_record: Box<Account<'info, GlobalXnRecord>>,
+    #[account(
+        seeds = [b"xn-miner-params", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub params: Box<Account<'info, MinerParams>>,
+    /// CHECK: address checked by seeds under the legacy program, owner and layout by the handler
+    #[account(
+        seeds = [
+            b"xn-by-sol",
+            user.key().as_ref(),
+            kind.to_be_bytes().as_slice(),
+            legacy_program.key().as_ref(),
+        ],
+        bump,
+        seeds::program = legacy_program.key(),
+    )]
+    pub legacy_record: UncheckedAccount<'info>,
+    /// CHECK: checked against `LEGACY_MINERS` by the handler
+    pub legacy_program: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        space = 8 + UserSolXnRecord::INIT_SPACE,
+        payer = user,
+        seeds = [
+            b"xn-by-sol",
+            user.key().as_ref(),
+            kind.to_be_bytes().as_slice(),
+            ID.as_ref(),
+        ],
+        bump
+    )]
+    pub xn_by_sol: Box<Account<'info, UserSolXnRecord>>,
+    // totals of the legacy record at the last import
+    #[account(
+        init_if_needed,
+        space = 8 + LegacyImport::INIT_SPACE,
+        payer = user,
+        seeds = [
+            b"xn-legacy-import",
+            user.key().as_ref(),
+            kind.to_be_bytes().as_slice(),
+        ],
+        bump
+    )]
+    pub legacy_import: Box<Account<'info, LegacyImport>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: u8)]
+pub struct CrankTotals<'info> {
+    #[account(
+        mut,
+        seeds = [b"xn-miner-global", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub global_xn_record: Box<Account<'info, GlobalXnRecord>>,
+    #[account(
+        seeds = [b"xn-miner-params", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub params: Box<Account<'info, MinerParams>>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: u8)]
+pub struct CloseUserRecord<'info> {
+    #[account(
+        seeds = [b"xn-miner-global", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub global_xn_record: Box<Account<'info, GlobalXnRecord>>,
+    #[account(
+        seeds = [b"xn-miner-params", kind.to_be_bytes().as_slice()],
+        bump,
+    )]
+    pub params: Box<Account<'info, MinerParams>>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            b"xn-by-sol",
+            user.key().as_ref(),
+            kind.to_be_bytes().as_slice(),
+            ID.as_ref(),
+        ],
+        bump
+    )]
+    pub xn_by_sol: Box<Account<'info, UserSolXnRecord>>,
+    /// CHECK: address checked by seeds under the minter, owner and layout by `minted_points`
+    #[account(
+        seeds = [b"sol-xen-minted", user.key().as_ref()],
+        bump,
+        seeds::program = MINTER,
+    )]
+    pub user_tokens_record: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[account]
+#[derive(InitSpace,Debug)]
+pub struct MinerConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[account]
+#[derive(InitSpace,Debug)]
+pub struct MinerParams {
+    pub version: u8,
+    pub kind: u8,
+    pub max_hashes: u8,
+    #[max_len(MAX_PATTERN_LEN)]
+    pub hash_pattern: String,
+    #[max_len(MAX_PATTERN_LEN)]
+    pub superhash_pattern: String,
+    pub superhash_x: u16,
+    pub amp_start: u16,
+    pub amp_cycle_slots: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub mix_slot_hash: bool,
+}
+
+impl MinerParams {
+    /// Slots per AMP cycle: `amp_cycle_slots + 1`, same as when each `mine_hashes` past a cycle decayed AMP itself
+    /// with a tx in every slot (see `GlobalXnRecord::pending_decays` for gaps)
+    pub fn cycle_slots(&self) -> u64 {
+        self.amp_cycle_slots.saturating_add(1)
+    }
+
+    pub fn set(&mut self, args: MinerParamsArgs) {
+        self.max_hashes = args.max_hashes;
+        self.hash_pattern = args.hash_pattern;
+        self.superhash_pattern = args.superhash_pattern;
+        self.superhash_x = args.superhash_x;
+        self.amp_start = args.amp_start;
+        self.amp_cycle_slots = args.amp_cycle_slots;
+        self.start_slot = args.start_slot;
+        self.end_slot = args.end_slot;
+        self.mix_slot_hash = args.mix_slot_hash;
+    }
+
+    pub fn args(&self) -> MinerParamsArgs {
+        MinerParamsArgs {
+            max_hashes: self.max_hashes,
+            hash_pattern: self.hash_pattern.clone(),
+            superhash_pattern: self.superhash_pattern.clone(),
+            superhash_x: self.superhash_x,
+            amp_start: self.amp_start,
+            amp_cycle_slots: self.amp_cycle_slots,
+            start_slot: self.start_slot,
+            end_slot: self.end_slot,
+            mix_slot_hash: self.mix_slot_hash,
+        }
+    }
 }
 
 #[account]
 #[derive(InitSpace,Debug)]
 pub struct UserEthXnRecord {
+    pub version: u8,
     pub hashes: u64,
-    pub superhashes: u32,
+    pub superhashes: u64,
 }
 
 #[account]
 #[derive(InitSpace,Debug)]
 pub struct UserSolXnRecord {
+    pub version: u8,
     pub hashes: u64,
-    pub superhashes: u32,
+    pub superhashes: u64,
+    pub points: u128
+}
+
+/// Totals of the user's legacy record at their last import into the record of this program
+#[account]
+#[derive(InitSpace,Debug)]
+pub struct LegacyImport {
+    pub legacy_program: Pubkey,
+    pub hashes: u64,
+    pub superhashes: u64,
     pub points: u128
 }
 
 #[account]
 #[derive(InitSpace,Debug)]
 pub struct GlobalXnRecord {
+    pub version: u8,
     pub amp: u16,
     pub last_amp_slot: u64,
     pub nonce: [u8; 4],
     pub kind: u8,
     pub hashes: u64,
-    pub superhashes: u32,
+    pub superhashes: u64,
     pub points: u128
 }
 
-pub fn find_hashes(slot: u64, nonce: [u8; 4]) -> (u8, u8) {
-    let current_slot = slot;
-    msg!("Current slot: {}", current_slot);
+/// Hashes mined with one of the kind's shards (see `shard_index`) since its last `crank_totals`, and the nonce
+/// its next `mine_hashes` uses
+#[account]
+#[derive(InitSpace,Debug)]
+pub struct GlobalXnShard {
+    pub version: u8,
+    pub kind: u8,
+    pub shard: u8,
+    pub nonce: [u8; 4],
+    pub hashes: u64,
+    pub superhashes: u64,
+    pub points: u128
+}
+
+/// Shard the `mine_hashes` txs of `user` go to
+pub fn shard_index(user: &Pubkey) -> u8 {
+    user.to_bytes()[0] % SHARDS
+}
+
+impl GlobalXnRecord {
+    /// AMP cycles (see `MinerParams::cycle_slots`) completed since `last_amp_slot`, not stored by `crank_totals` yet.
+    /// Every elapsed cycle counts, where per-tx decay used to count one per tx however long the gap before it
+    pub fn pending_decays(&self, slot: u64, params: &MinerParams) -> u64 {
+        slot.saturating_sub(self.last_amp_slot) / params.cycle_slots()
+    }
+
+    /// AMP at `slot`, including the decay not stored yet
+    pub fn amp_at(&self, slot: u64, params: &MinerParams) -> u16 {
+        let decays = self.pending_decays(slot, params).min(self.amp as u64) as u16;
+        self.amp - decays
+    }
+
+    /// Stores the pending decay, keeping `last_amp_slot` on cycle boundaries; false if there was none
+    pub fn store_decay(&mut self, slot: u64, params: &MinerParams) -> bool {
+        let decays = self.pending_decays(slot, params);
+        if decays == 0 || self.amp == 0 {
+            return false;
+        }
+        self.amp = self.amp_at(slot, params);
+        self.last_amp_slot += decays * params.cycle_slots();
+        true
+    }
+
+    /// Nonce a new shard starts from, derived from the kind's one so that each shard starts from a different one
+    pub fn initial_shard_nonce(&self, shard: u8) -> [u8; 4] {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.nonce);
+        hasher.update(shard.to_le_bytes());
+        hasher.finalize()[0..4].try_into().unwrap()
+    }
+
+    /// AMP cycles left before mining is over: AMP decays by one per cycle,
+    /// and no cycle can start at or past a non-zero `end_slot`
+    pub fn remaining_cycles(&self, slot: u64, params: &MinerParams) -> u64 {
+        let by_amp = self.amp_at(slot, params) as u64;
+        if params.end_slot == 0 {
+            return by_amp;
+        }
+        if slot >= params.end_slot {
+            return 0;
+        }
+        by_amp.min((params.end_slot - slot).div_ceil(params.cycle_slots()))
+    }
+}
+
+/// Points credited for a `mine_hashes` tx (with 9 decimals); `None` on overflow
+pub fn mined_points(amp: u16, hashes: u8, superhashes: u8, superhash_x: u16) -> Option<u128> {
+    let per_hash = POINTS_DECIMALS.checked_mul(amp as u128)?;
+    let per_superhash = per_hash.checked_mul(superhash_x as u128)?;
+    per_hash
+        .checked_mul(hashes as u128)?
+        .checked_add(per_superhash.checked_mul(superhashes as u128)?)
+}
+
+/// Miner record with a version byte; the legacy miners may still hold it in older layouts, see `read_record`
+pub trait VersionedRecord: AccountSerialize + Discriminator + Space + Sized {
+    /// Record in the current layout from the data (past the discriminator) of an older one;
+    /// `None` if `data` is not a known older layout
+    fn upgrade(data: &[u8]) -> Option<Self>;
+}
+
+impl VersionedRecord for UserEthXnRecord {
+    fn upgrade(data: &[u8]) -> Option<Self> {
+        let (hashes, superhashes) = match data.len() {
+            12 => <(u64, u32)>::try_from_slice(data).map(|(h, sh)| (h, sh as u64)).ok()?,
+            16 => <(u64, u64)>::try_from_slice(data).ok()?,
+            _ => return None,
+        };
+        Some(UserEthXnRecord { version: RECORD_VERSION, hashes, superhashes })
+    }
+}
+
+impl VersionedRecord for UserSolXnRecord {
+    fn upgrade(data: &[u8]) -> Option<Self> {
+        let (hashes, superhashes, points) = match data.len() {
+            28 => <(u64, u32, u128)>::try_from_slice(data).map(|(h, sh, p)| (h, sh as u64, p)).ok()?,
+            32 => <(u64, u64, u128)>::try_from_slice(data).ok()?,
+            _ => return None,
+        };
+        Some(UserSolXnRecord { version: RECORD_VERSION, hashes, superhashes, points })
+    }
+}
+
+impl VersionedRecord for GlobalXnRecord {
+    fn upgrade(data: &[u8]) -> Option<Self> {
+        // amp, last_amp_slot, nonce and kind haven't changed
+        let (amp, last_amp_slot, nonce, kind) = <(u16, u64, [u8; 4], u8)>::deserialize(&mut &data[..])
+            .ok()?;
+        let rest = &data[15..];
+        let (hashes, superhashes, points) = match data.len() {
+            43 => <(u64, u32, u128)>::try_from_slice(rest).map(|(h, sh, p)| (h, sh as u64, p)).ok()?,
+            47 => <(u64, u64, u128)>::try_from_slice(rest).ok()?,
+            _ => return None,
+        };
+        Some(GlobalXnRecord { version: RECORD_VERSION, amp, last_amp_slot, nonce, kind, hashes, superhashes, points })
+    }
+}
+
+/// Decodes account data holding a record in the current or in any older layout
+pub fn read_record<T: VersionedRecord + AccountDeserialize>(data: &[u8]) -> Option<T> {
+    if data.len() <= 8 || data[..8] != T::DISCRIMINATOR {
+        return None;
+    }
+    if data.len() == 8 + T::INIT_SPACE {
+        T::try_deserialize(&mut &data[..]).ok()
+    } else {
+        T::upgrade(&data[8..])
+    }
+}
+
+// mines on the user's shard and credits `eth_address`, once the handlers have checked their own args
+fn mine(accounts: &mut MineHashes, eth_address: [u8; 20], kind: u8, iterations: u8) -> Result<()> {
+    // Get the current slot number
+    let slot = Clock::get().unwrap().slot;
+    let params = &accounts.params;
+    require!(slot > params.start_slot, SolXenError::MintIsNotActive);
+    require!(params.end_slot == 0 || slot < params.end_slot, SolXenError::MiningIsOver);
+    require!(iterations > 0 && iterations <= params.max_hashes, SolXenError::InvalidIterations);
+
+    verbose_msg!("Using slot #{}", slot);
+
+    require!(slot > 0, SolXenError::ZeroSlotValue);
+
+    // AMP decays by one per cycle; `crank_totals` stores the decay, the global record is only read here
+    let global = &accounts.global_xn_record;
+    let amp = global.amp_at(slot, params);
+    // zero AMP means zero points: stop charging fees for nothing
+    require!(amp > 0, SolXenError::MiningIsOver);
+
+    // txs of different users mostly land on different shards, so they don't contend for the same account
+    let shard = &mut accounts.xn_shard;
+    if shard.version == 0 {
+        let index = shard_index(accounts.user.key);
+        shard.version = SHARD_VERSION;
+        shard.kind = kind;
+        shard.shard = index;
+        shard.nonce = global.initial_shard_nonce(index);
+    }
+
+    // Find hashes, mixing in the parent slot hash if enabled: it is only known once the parent slot is done,
+    // which narrows working out the outcome for a targeted slot down to the slot before it
+    let nonce = shard.nonce;
+    let slot_hash = if params.mix_slot_hash {
+        let slot_hash = recent_slot_hash(&accounts.slot_hashes.try_borrow_data()?)
+            .ok_or(SolXenError::SlotHashUnavailable)?;
+        Some(slot_hash)
+    } else {
+        None
+    };
+    let (hashes, superhashes) = find_hashes(slot, nonce, slot_hash, iterations, params);
+
+    // Calculate points convertible to solXEN tokens
+    let points = mined_points(amp, hashes, superhashes, params.superhash_x)
+        .ok_or(SolXenError::PointsOverflow)?;
+    verbose_msg!("Mined hashes {} superhashes {} points {} nonce {:?}", hashes, superhashes, points, nonce);
+
+    // Update user scores by eth address
+    let xn_by_eth = &mut accounts.xn_by_eth;
+    xn_by_eth.version = RECORD_VERSION;
+    xn_by_eth.hashes = xn_by_eth.hashes.checked_add(hashes as u64).ok_or(SolXenError::PointsOverflow)?;
+    xn_by_eth.superhashes = xn_by_eth.superhashes.checked_add(superhashes as u64).ok_or(SolXenError::PointsOverflow)?;
+
+    // Update user scores by sol address
+    let xn_by_sol = &mut accounts.xn_by_sol;
+    xn_by_sol.version = RECORD_VERSION;
+    xn_by_sol.hashes = xn_by_sol.hashes.checked_add(hashes as u64).ok_or(SolXenError::PointsOverflow)?;
+    xn_by_sol.superhashes = xn_by_sol.superhashes.checked_add(superhashes as u64).ok_or(SolXenError::PointsOverflow)?;
+    xn_by_sol.points = xn_by_sol.points.checked_add(points).ok_or(SolXenError::PointsOverflow)?;
+
+    // Update shard's scores accumulators, added to the kind's totals by `crank_totals`
+    let shard = &mut accounts.xn_shard;
+    shard.hashes = shard.hashes.checked_add(hashes as u64).ok_or(SolXenError::PointsOverflow)?;
+    shard.superhashes = shard.superhashes.checked_add(superhashes as u64).ok_or(SolXenError::PointsOverflow)?;
+    shard.points = shard.points.checked_add(points).ok_or(SolXenError::PointsOverflow)?;
+
+    // calculate and store new nonce
+    let mut hasher = Keccak256::new();
+    hasher.update(accounts.user.key.to_bytes());
+    hasher.update(hashes.to_le_bytes());
+    hasher.update(superhashes.to_le_bytes());
+    hasher.update(slot.to_le_bytes());
+    let next_nonce = hasher.finalize();
+    shard.nonce = next_nonce[0..4].try_into().unwrap();
+
+    // Emit hash tx record
+    let (xn_by_sol, global) = (&accounts.xn_by_sol, &accounts.global_xn_record);
+    emit!(HashEventV2 {
+        slot,
+        kind,
+        user: *accounts.user.key,
+        eth_account: eth_address,
+        amp,
+        nonce,
+        hashes,
+        superhashes,
+        points,
+        user_hashes: xn_by_sol.hashes,
+        user_superhashes: xn_by_sol.superhashes,
+        user_points: xn_by_sol.points,
+        global_hashes: global.hashes,
+        global_superhashes: global.superhashes,
+        global_points: global.points,
+    });
+    // kept for existing listeners, superseded by `HashEventV2`
+    emit!(HashEvent {
+        slot,
+        user: *accounts.user.key,
+        eth_account: eth_address,
+        hashes,
+        superhashes,
+        // fits as long as params stay within bounds, see `MinerParamsArgs::validate`
+        points: u64::try_from(points).map_err(|_| SolXenError::PointsOverflow)?,
+    });
+
+    Ok(())
+}
+
+/// Points of `kind` the user has minted so far, read from their `sol_xen_minter::UserTokensRecord`
+/// (the minter depends on this crate, so its layout is read by hand: `points_counters: [u128; 4]` come first);
+/// zero if the user has never minted
+pub fn minted_points(user_tokens_record: &AccountInfo, kind: u8) -> Result<u128> {
+    if user_tokens_record.data_is_empty() {
+        return Ok(0);
+    }
+    require_keys_eq!(*user_tokens_record.owner, MINTER, SolXenError::InvalidRecord);
+    let data = user_tokens_record.try_borrow_data()?;
+    let discriminator = solana_program::hash::hash(b"account:UserTokensRecord").to_bytes();
+    let offset = 8 + 16 * kind as usize;
+    require!(
+        data.len() >= offset + 16 && data[..8] == discriminator[..8],
+        SolXenError::InvalidRecord
+    );
+    Ok(u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap()))
+}
+
+/// Hash of the most recent slot (the parent of the current one) from raw `SlotHashes` sysvar data:
+/// a `u64` count, then `(slot: u64, hash: [u8; 32])` entries, newest first. `None` if there are no entries
+pub fn recent_slot_hash(slot_hashes: &[u8]) -> Option<[u8; 32]> {
+    let count = u64::from_le_bytes(slot_hashes.get(..8)?.try_into().unwrap());
+    if count == 0 {
+        return None;
+    }
+    slot_hashes.get(16..48).map(|hash| hash.try_into().unwrap())
+}
+
+/// (hashes, superhashes) among `iterations` Keccak digests of `nonce`, `slot`, `slot_hash` (if any) and the iteration
+pub fn find_hashes(slot: u64, nonce: [u8; 4], slot_hash: Option<[u8; 32]>, iterations: u8, params: &MinerParams) -> (u8, u8) {
+    verbose_msg!("Current slot: {}", slot);
     let mut hashes = 0;
     let mut superhashes = 0;
 
-    for i in 0..MAX_HASHES {
+    for i in 0..iterations {
         let mut hasher = Keccak256::new();
         hasher.update(nonce.as_slice());
         hasher.update(slot.to_le_bytes());
+        if let Some(slot_hash) = slot_hash {
+            hasher.update(slot_hash);
+        }
         hasher.update(i.to_le_bytes());
         let result = hasher.finalize();
+        #[cfg(not(feature = "hex-matcher"))]
+        let matches = |pattern: &str| contains_hex_pattern(&result, pattern.as_bytes());
+        // the hex string match this replaced, to compare the compute units of both
+        #[cfg(feature = "hex-matcher")]
         let hex_string = format!("{:x}", result);
-        if hex_string.contains(SUPERHASH_PATTERN) {
-            msg!("Found '{}' in hash at iteration {}: {}", SUPERHASH_PATTERN, i, hex_string);
+        #[cfg(feature = "hex-matcher")]
+        let matches = |pattern: &str| hex_string.contains(pattern);
+        if matches(&params.superhash_pattern) {
+            verbose_msg!("Found '{}' in hash at iteration {}: {:x}", params.superhash_pattern, i, result);
             superhashes += 1;
-        } else if hex_string.contains(HASH_PATTERN) {
-            msg!("Found '{}' in hash at iteration {}: {}", HASH_PATTERN, i, hex_string);
+        } else if matches(&params.hash_pattern) {
+            verbose_msg!("Found '{}' in hash at iteration {}: {:x}", params.hash_pattern, i, result);
             hashes += 1;
         }
     }
     if superhashes == 0 && hashes == 0 {
-        msg!("Found zero targets in hashes after {} iterations", MAX_HASHES);
+        verbose_msg!("Found zero targets in hashes after {} iterations", iterations);
+    }
+    (hashes, superhashes)
+}
+
+/// Same as `format!("{:x}", hash).contains(pattern)`, but scans the nibbles of `hash` in place
+/// instead of allocating its hex string
+pub fn contains_hex_pattern(hash: &[u8], pattern: &[u8]) -> bool {
+    let nibbles = hash.len() * 2;
+    if pattern.len() > nibbles {
+        return false;
+    }
+    (0..=nibbles - pattern.len()).any(|start| {
+        pattern
+            .iter()
+            .enumerate()
+            .all(|(j, &c)| hex_digit_value(c) == Some(nibble_at(hash, start + j)))
+    })
+}
+
+fn nibble_at(bytes: &[u8], i: usize) -> u8 {
+    let byte = bytes[i / 2];
+    if i & 1 == 0 {
+        byte >> 4
+    } else {
+        byte & 0x0f
+    }
+}
+
+// lowercase only, to match `{:x}` formatting
+fn hex_digit_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        _ => None,
     }
-    return (hashes, superhashes);
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct EthAccount {
-    pub address: [u8; 20],
-    pub address_str: String,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MinerParamsArgs {
+    pub max_hashes: u8,
+    pub hash_pattern: String,
+    pub superhash_pattern: String,
+    pub superhash_x: u16,
+    pub amp_start: u16,
+    pub amp_cycle_slots: u64,
+    pub start_slot: u64,
+    /// 0 keeps mining open until AMP decays to 0
+    pub end_slot: u64,
+    /// Mix the parent slot hash from `SlotHashes` into `find_hashes`, see `recent_slot_hash`
+    pub mix_slot_hash: bool,
+}
+
+impl MinerParamsArgs {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.max_hashes > 0 && self.max_hashes <= MAX_HASHES_LIMIT, SolXenError::InvalidMinerParams);
+        require!(is_hex_pattern(&self.hash_pattern), SolXenError::InvalidMinerParams);
+        require!(is_hex_pattern(&self.superhash_pattern), SolXenError::InvalidMinerParams);
+        // superhashes have to be rarer than hashes
+        require!(self.superhash_pattern.len() > self.hash_pattern.len(), SolXenError::InvalidMinerParams);
+        require!(self.superhash_x > 0 && self.superhash_x <= MAX_SUPERHASH_X, SolXenError::InvalidMinerParams);
+        require!(self.amp_start > 0 && self.amp_start <= MAX_AMP_START, SolXenError::InvalidMinerParams);
+        require!(self.amp_cycle_slots > 0, SolXenError::InvalidMinerParams);
+        require!(self.end_slot == 0 || self.end_slot > self.start_slot, SolXenError::InvalidMinerParams);
+        Ok(())
+    }
+}
+
+// non-empty lowercase hex, as produced by `format!("{:x}")`
+fn is_hex_pattern(pattern: &str) -> bool {
+    !pattern.is_empty()
+        && pattern.len() <= MAX_PATTERN_LEN
+        && pattern.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MiningStatus {
+    pub kind: u8,
+    pub slot: u64,
+    pub amp: u16,
+    pub end_slot: u64,
+    pub remaining_cycles: u64,
+    pub is_over: bool,
+}
+
+#[event]
+pub struct ParamsUpdated {
+    kind: u8,
+    admin: Pubkey,
+    old: MinerParamsArgs,
+    new: MinerParamsArgs,
 }
 
 #[event]
@@ -239,11 +1088,73 @@ pub struct HashEvent {
     points: u64
 }
 
+/// Emitted by `mine_hashes` along with `HashEvent`: the kind, AMP and nonce the hashes were found with,
+/// the points credited (untruncated), the user's totals after the tx and the kind's ones as of the last `crank_totals`
+#[event]
+pub struct HashEventV2 {
+    slot: u64,
+    kind: u8,
+    user: Pubkey,
+    eth_account: [u8; 20],
+    amp: u16,
+    nonce: [u8; 4],
+    hashes: u8,
+    superhashes: u8,
+    points: u128,
+    user_hashes: u64,
+    user_superhashes: u64,
+    user_points: u128,
+    global_hashes: u64,
+    global_superhashes: u64,
+    global_points: u128,
+}
+
+#[event]
+pub struct AmpDecayed {
+    kind: u8,
+    slot: u64,
+    amp: u16,
+    previous_amp_slot: u64,
+}
+
+/// Emitted by `crank_totals`: what was added from `shards` shards, and the kind's totals after that
+#[event]
+pub struct TotalsCranked {
+    kind: u8,
+    slot: u64,
+    shards: u8,
+    hashes: u64,
+    superhashes: u64,
+    points: u128,
+    global_hashes: u64,
+    global_superhashes: u64,
+    global_points: u128,
+}
+
+#[event]
+pub struct MinerInitialized {
+    kind: u8,
+    admin: Pubkey,
+    slot: u64,
+    nonce: [u8; 4],
+    params: MinerParamsArgs,
+}
+
+#[event]
+pub struct UserRecordClosed {
+    kind: u8,
+    user: Pubkey,
+    hashes: u64,
+    superhashes: u64,
+    points: u128,
+    lamports: u64,
+}
+
 #[error_code]
 pub enum SolXenError {
     #[msg("solXEN Mint has been already initialized")]
     MintIsAlreadyActive,
-    #[msg("solXEN Mint has not yet started or is over")]
+    #[msg("solXEN Mint has not yet started")]
     MintIsNotActive,
     #[msg("Slot value is Zero")]
     ZeroSlotValue,
@@ -253,4 +1164,24 @@ pub enum SolXenError {
     InvalidEthAddressChecksum,
     #[msg("Ethereum address data doesnt match")]
     InvalidEthAddressData,
+    #[msg("Signer is not the miner admin")]
+    NotAdmin,
+    #[msg("Signer is not the pending miner admin")]
+    NotPendingAdmin,
+    #[msg("Miner params are out of bounds")]
+    InvalidMinerParams,
+    #[msg("solXEN mining is over")]
+    MiningIsOver,
+    #[msg("Points or hash counters overflow")]
+    PointsOverflow,
+    #[msg("Not a miner record of the expected type or layout")]
+    InvalidRecord,
+    #[msg("solXEN mining is not over yet")]
+    MiningIsNotOver,
+    #[msg("User record has points not minted yet")]
+    PointsNotMinted,
+    #[msg("No recent slot hash to mix in")]
+    SlotHashUnavailable,
+    #[msg("Iterations have to be between 1 and the kind's max_hashes")]
+    InvalidIterations,
 }
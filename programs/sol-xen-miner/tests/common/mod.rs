@@ -0,0 +1,38 @@
+//! Fixtures shared by the miner tests, and by the SDK tests checking its copies of the program logic
+#![allow(dead_code)]
+
+use sol_xen_miner::{GlobalXnRecord, MinerParams, RECORD_VERSION};
+
+/// Kind 0 params with the given patterns, otherwise as set up by `admin/init_miner.ts`
+pub fn params(hash_pattern: &str, superhash_pattern: &str, max_hashes: u8) -> MinerParams {
+    MinerParams {
+        version: 2,
+        kind: 0,
+        max_hashes,
+        hash_pattern: hash_pattern.to_string(),
+        superhash_pattern: superhash_pattern.to_string(),
+        superhash_x: 250,
+        amp_start: 300,
+        amp_cycle_slots: 100_000,
+        start_slot: 0,
+        end_slot: 0,
+        mix_slot_hash: false,
+    }
+}
+
+pub fn default_params() -> MinerParams {
+    params("420", "42069", 72)
+}
+
+pub fn global(amp: u16, last_amp_slot: u64) -> GlobalXnRecord {
+    GlobalXnRecord {
+        version: RECORD_VERSION,
+        amp,
+        last_amp_slot,
+        nonce: [1, 2, 3, 4],
+        kind: 0,
+        hashes: 0,
+        superhashes: 0,
+        points: 0,
+    }
+}
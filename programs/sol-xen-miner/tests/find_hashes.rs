@@ -0,0 +1,128 @@
+mod common;
+
+use proptest::prelude::*;
+use common::params;
+use sol_xen_miner::{contains_hex_pattern, find_hashes, recent_slot_hash, MinerParams};
+use sha3::{Digest, Keccak256};
+
+// `find_hashes` as it was before the nibble matcher, kept as the reference
+fn find_hashes_hex(slot: u64, nonce: [u8; 4], slot_hash: Option<[u8; 32]>, iterations: u8, params: &MinerParams) -> (u8, u8) {
+    let mut hashes = 0;
+    let mut superhashes = 0;
+    for i in 0..iterations {
+        let mut hasher = Keccak256::new();
+        hasher.update(nonce.as_slice());
+        hasher.update(slot.to_le_bytes());
+        if let Some(slot_hash) = slot_hash {
+            hasher.update(slot_hash);
+        }
+        hasher.update(i.to_le_bytes());
+        let hex_string = format!("{:x}", hasher.finalize());
+        if hex_string.contains(&params.superhash_pattern) {
+            superhashes += 1;
+        } else if hex_string.contains(&params.hash_pattern) {
+            hashes += 1;
+        }
+    }
+    (hashes, superhashes)
+}
+
+#[test]
+fn matches_hex_strings_on_default_params() {
+    let params = params("420", "42069", 72);
+    let mut found = (0u32, 0u32);
+    for slot in 268_484_759..268_484_759 + 2_000 {
+        let nonce = (slot as u32).wrapping_mul(2_654_435_761).to_le_bytes();
+        let result = find_hashes(slot, nonce, None, params.max_hashes, &params);
+        assert_eq!(result, find_hashes_hex(slot, nonce, None, params.max_hashes, &params), "slot {slot}");
+        found.0 += result.0 as u32;
+        found.1 += result.1 as u32;
+    }
+    // make sure the sample actually exercises both branches
+    assert!(found.0 > 0 && found.1 > 0);
+}
+
+// (slot, nonce, mixed) -> (hashes, superhashes) on the default params; listed in the README.
+// Mixed vectors use keccak256(slot as u64 LE) as the slot hash, standing in for the one read from `SlotHashes`
+type Vector = (u64, [u8; 4], bool, (u8, u8));
+
+const VECTORS: [Vector; 6] = [
+    (268_484_781, [157, 252, 193, 150], false, (1, 0)),
+    (268_484_781, [157, 252, 193, 150], true, (1, 1)),
+    (268_484_921, [105, 137, 24, 29], false, (0, 1)),
+    (268_484_921, [105, 137, 24, 29], true, (0, 0)),
+    (280_123_456, [64, 68, 197, 76], false, (3, 0)),
+    (280_123_456, [64, 68, 197, 76], true, (1, 0)),
+];
+
+fn vector_slot_hash(slot: u64) -> [u8; 32] {
+    Keccak256::digest(slot.to_le_bytes()).into()
+}
+
+#[test]
+fn matches_test_vectors() {
+    let params = params("420", "42069", 72);
+    for (slot, nonce, mixed, expected) in VECTORS {
+        let slot_hash = mixed.then(|| vector_slot_hash(slot));
+        assert_eq!(find_hashes(slot, nonce, slot_hash, 72, &params), expected, "slot {slot} mixed {mixed}");
+    }
+}
+
+#[test]
+fn reads_most_recent_slot_hash() {
+    let mut data = 2u64.to_le_bytes().to_vec();
+    data.extend(101u64.to_le_bytes());
+    data.extend([7u8; 32]);
+    data.extend(100u64.to_le_bytes());
+    data.extend([8u8; 32]);
+    assert_eq!(recent_slot_hash(&data), Some([7u8; 32]));
+    assert_eq!(recent_slot_hash(&0u64.to_le_bytes()), None);
+    assert_eq!(recent_slot_hash(&[]), None);
+}
+
+#[test]
+fn pattern_at_hash_edges() {
+    let hash = [0x42, 0x0a, 0xbc, 0xde, 0xf0, 0x12, 0x34, 0x56];
+    assert!(contains_hex_pattern(&hash, b"420"));
+    assert!(contains_hex_pattern(&hash, b"20a"));
+    assert!(contains_hex_pattern(&hash, b"3456"));
+    assert!(contains_hex_pattern(&hash, b"6"));
+    assert!(contains_hex_pattern(&hash, b"420abcdef0123456"));
+    assert!(!contains_hex_pattern(&hash, b"420abcdef01234560"));
+    assert!(!contains_hex_pattern(&hash, b"ABC"));
+    assert!(!contains_hex_pattern(&hash, b"42g"));
+}
+
+proptest! {
+    #[test]
+    fn matches_hex_strings(
+        slot in any::<u64>(),
+        nonce in any::<[u8; 4]>(),
+        hash_pattern in "[0-9a-f]{1,3}",
+        superhash_suffix in "[0-9a-f]{1,2}",
+        slot_hash in any::<Option<[u8; 32]>>(),
+        iterations in 1u8..=128,
+    ) {
+        let superhash_pattern = format!("{hash_pattern}{superhash_suffix}");
+        let params = params(&hash_pattern, &superhash_pattern, 128);
+        prop_assert_eq!(
+            find_hashes(slot, nonce, slot_hash, iterations, &params),
+            find_hashes_hex(slot, nonce, slot_hash, iterations, &params)
+        );
+    }
+
+    // a call with fewer iterations finds what the first ones of a longer call find
+    #[test]
+    fn iterations_are_a_prefix(slot in any::<u64>(), nonce in any::<[u8; 4]>(), iterations in 1u8..=72) {
+        let params = params("42", "420", 72);
+        let (hashes, superhashes) = find_hashes(slot, nonce, None, iterations, &params);
+        let (all_hashes, all_superhashes) = find_hashes(slot, nonce, None, 72, &params);
+        prop_assert!(hashes <= all_hashes && superhashes <= all_superhashes);
+    }
+
+    #[test]
+    fn contains_matches_str_contains(hash in any::<[u8; 32]>(), pattern in "[0-9a-fA-G]{0,6}") {
+        let hex_string = hash.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        prop_assert_eq!(contains_hex_pattern(&hash, pattern.as_bytes()), hex_string.contains(&pattern));
+    }
+}
@@ -0,0 +1,32 @@
+use proptest::prelude::*;
+use sol_xen_miner::{mined_points, MAX_AMP_START, MAX_HASHES_LIMIT, MAX_SUPERHASH_X};
+
+#[test]
+fn max_params_fit_hash_event() {
+    let points = mined_points(MAX_AMP_START, 0, MAX_HASHES_LIMIT, MAX_SUPERHASH_X).unwrap();
+    assert_eq!(points, 1_000_000_000 * 1_000 * 1_000 * 128);
+    assert!(u64::try_from(points).is_ok());
+    assert!(mined_points(u16::MAX, u8::MAX, u8::MAX, u16::MAX).is_some());
+}
+
+proptest! {
+    #[test]
+    fn points_never_overflow(amp in any::<u16>(), hashes in any::<u8>(), superhashes in any::<u8>(), superhash_x in any::<u16>()) {
+        let points = mined_points(amp, hashes, superhashes, superhash_x).unwrap();
+        let expected = 1_000_000_000u128 * amp as u128 * (hashes as u128 + superhash_x as u128 * superhashes as u128);
+        prop_assert_eq!(points, expected);
+    }
+
+    #[test]
+    fn points_within_bounds_fit_u64(
+        amp in 0..=MAX_AMP_START,
+        hashes in 0..=MAX_HASHES_LIMIT,
+        superhash_x in 0..=MAX_SUPERHASH_X,
+        superhashes_share in 0.0f64..=1.0,
+    ) {
+        // a tx can't find more hashes + superhashes than `max_hashes`
+        let superhashes = (hashes as f64 * superhashes_share) as u8;
+        let points = mined_points(amp, hashes - superhashes, superhashes, superhash_x).unwrap();
+        prop_assert!(u64::try_from(points).is_ok());
+    }
+}
@@ -0,0 +1,118 @@
+use anchor_lang::{prelude::*, solana_program::hash::hash, Discriminator};
+use proptest::prelude::*;
+use sol_xen_miner::{
+    minted_points, read_record, GlobalXnRecord, UserEthXnRecord, UserSolXnRecord, VersionedRecord, MINTER,
+    RECORD_VERSION,
+};
+
+// older layout read into the current one, round-tripped through account data
+fn upgrade<T: VersionedRecord + AccountDeserialize>(legacy_fields: impl AnchorSerialize) -> T {
+    let legacy = legacy_fields.try_to_vec().unwrap();
+    let upgraded = T::upgrade(&legacy).unwrap();
+    let mut data = vec![0u8; 8 + T::INIT_SPACE];
+    upgraded.try_serialize(&mut &mut data[..]).unwrap();
+    assert_eq!(data[..8], T::DISCRIMINATOR);
+    assert_eq!(data[8], RECORD_VERSION);
+    T::try_deserialize(&mut data.as_slice()).unwrap()
+}
+
+#[test]
+fn unknown_layouts_are_rejected() {
+    assert!(UserEthXnRecord::upgrade(&[0u8; 13]).is_none());
+    assert!(UserSolXnRecord::upgrade(&[0u8; 33]).is_none());
+    assert!(GlobalXnRecord::upgrade(&[0u8; 10]).is_none());
+    assert!(GlobalXnRecord::upgrade(&[0u8; 48]).is_none());
+}
+
+#[test]
+fn read_record_accepts_all_versions() {
+    let discriminator = UserSolXnRecord::DISCRIMINATOR.as_slice();
+    let v0 = [discriminator, &(7u64, 1u32, 42u128).try_to_vec().unwrap()].concat();
+    let v2 = [discriminator, &(RECORD_VERSION, 7u64, 1u64, 42u128).try_to_vec().unwrap()].concat();
+    for data in [v0, v2] {
+        let record = read_record::<UserSolXnRecord>(&data).unwrap();
+        assert_eq!((record.hashes, record.superhashes, record.points), (7, 1, 42));
+    }
+
+    let forged = [UserEthXnRecord::DISCRIMINATOR.as_slice(), &(7u64, 1u32, 42u128).try_to_vec().unwrap()].concat();
+    assert!(read_record::<UserSolXnRecord>(&forged).is_none());
+}
+
+#[test]
+fn minted_points_are_read_from_the_minter_record() {
+    // legacy sol_xen_minter::UserTokensRecord { points_counters: [u128; 4], tokens_minted: u128 }
+    let mut data = [
+        &hash(b"account:UserTokensRecord").to_bytes()[..8],
+        &([1u128, 2, 3, 4], 10u128).try_to_vec().unwrap(),
+    ]
+    .concat();
+    let key = Pubkey::new_unique();
+    let mut lamports = 1;
+    let record = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &MINTER, false, 0);
+    for kind in 0..4 {
+        assert_eq!(minted_points(&record, kind).unwrap(), kind as u128 + 1);
+    }
+
+    // current layout: { points_counters, tokens_minted, version: u8, points_converted: u128, points_remainder: u16 }
+    let mut data = [
+        &hash(b"account:UserTokensRecord").to_bytes()[..8],
+        &([5u128, 6, 7, 8], 10u128, 1u8, 26u128, 3u16).try_to_vec().unwrap(),
+    ]
+    .concat();
+    let mut lamports = 1;
+    let current = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &MINTER, false, 0);
+    for kind in 0..4 {
+        assert_eq!(minted_points(&current, kind).unwrap(), kind as u128 + 5);
+    }
+
+    let mut data = record.data.borrow().to_vec();
+    let (mut lamports, stranger) = (1, Pubkey::new_unique());
+    let forged = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &stranger, false, 0);
+    assert!(minted_points(&forged, 0).is_err());
+
+    // never minted
+    let (mut lamports, mut data) = (0, vec![]);
+    let system = anchor_lang::system_program::ID;
+    let missing = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &system, false, 0);
+    assert_eq!(minted_points(&missing, 0).unwrap(), 0);
+}
+
+proptest! {
+    #[test]
+    fn user_records_upgrade_from_all_versions(hashes in any::<u64>(), superhashes in any::<u32>(), points in any::<u128>()) {
+        let v0 = upgrade::<UserEthXnRecord>((hashes, superhashes));
+        let v1 = upgrade::<UserEthXnRecord>((hashes, superhashes as u64));
+        for record in [v0, v1] {
+            prop_assert_eq!((record.version, record.hashes, record.superhashes), (RECORD_VERSION, hashes, superhashes as u64));
+        }
+
+        let v0 = upgrade::<UserSolXnRecord>((hashes, superhashes, points));
+        let v1 = upgrade::<UserSolXnRecord>((hashes, superhashes as u64, points));
+        for record in [v0, v1] {
+            prop_assert_eq!(
+                (record.version, record.hashes, record.superhashes, record.points),
+                (RECORD_VERSION, hashes, superhashes as u64, points)
+            );
+        }
+    }
+
+    #[test]
+    fn global_record_upgrades_from_all_versions(
+        amp in any::<u16>(),
+        last_amp_slot in any::<u64>(),
+        nonce in any::<[u8; 4]>(),
+        kind in any::<u8>(),
+        hashes in any::<u64>(),
+        superhashes in any::<u32>(),
+        points in any::<u128>(),
+    ) {
+        let v0 = upgrade::<GlobalXnRecord>((amp, last_amp_slot, nonce, kind, hashes, superhashes, points));
+        let v1 = upgrade::<GlobalXnRecord>((amp, last_amp_slot, nonce, kind, hashes, superhashes as u64, points));
+        for record in [v0, v1] {
+            prop_assert_eq!(
+                (record.version, record.amp, record.last_amp_slot, record.nonce, record.kind, record.hashes, record.superhashes, record.points),
+                (RECORD_VERSION, amp, last_amp_slot, nonce, kind, hashes, superhashes as u64, points)
+            );
+        }
+    }
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::global;
+use proptest::prelude::*;
+use sol_xen_miner::{shard_index, MinerParams, SHARDS};
+use anchor_lang::prelude::Pubkey;
+
+fn params(amp_cycle_slots: u64) -> MinerParams {
+    MinerParams { amp_cycle_slots, ..common::default_params() }
+}
+
+#[test]
+fn users_spread_over_all_shards() {
+    let mut seen = [false; SHARDS as usize];
+    for i in 0..=255u8 {
+        let shard = shard_index(&Pubkey::new_from_array([i; 32]));
+        assert!(shard < SHARDS);
+        seen[shard as usize] = true;
+    }
+    assert!(seen.iter().all(|&seen| seen));
+}
+
+#[test]
+fn decay_stops_at_zero() {
+    let params = params(10);
+    let mut global = global(2, 0);
+    assert_eq!(global.amp_at(1_000, &params), 0);
+    assert!(global.store_decay(1_000, &params));
+    assert_eq!(global.amp, 0);
+    assert!(!global.store_decay(2_000, &params));
+}
+
+#[test]
+fn remaining_cycles_use_decay_cycle_length() {
+    let mut params = params(9);
+    params.end_slot = 100;
+    let global = global(300, 80);
+    // cycles of 10 slots start at 80 and 90 before the end slot
+    assert_eq!(global.remaining_cycles(80, &params), 2);
+    assert_eq!(global.remaining_cycles(90, &params), 1);
+    assert_eq!(global.remaining_cycles(99, &params), 1);
+    assert_eq!(global.remaining_cycles(100, &params), 0);
+}
+
+#[test]
+fn decay_counts_every_cycle_of_a_gap() {
+    let params = params(9);
+    let mut global = global(300, 0);
+    // first tx after 5 quiet cycles: per-tx decay took AMP to 299 only
+    assert_eq!(global.amp_at(50, &params), 295);
+    assert!(global.store_decay(55, &params));
+    // cycles stay on their boundaries instead of restarting at the tx slot
+    assert_eq!(global.last_amp_slot, 50);
+    assert_eq!(global.amp_at(59, &params), 295);
+    assert_eq!(global.amp_at(60, &params), 294);
+}
+
+#[test]
+fn schedule_changes_apply_from_the_last_cycle() {
+    let (old, new) = (params(9), params(19));
+    let mut global = global(300, 0);
+    // what `update_params` does at slot 55 before switching to 20-slot cycles
+    assert!(global.store_decay(55, &old));
+    assert_eq!((global.amp, global.last_amp_slot), (295, 50));
+    assert_eq!(global.amp_at(69, &new), 295);
+    assert_eq!(global.amp_at(70, &new), 294);
+    // without it the new cycle length would apply back to slot 0
+    assert_eq!(common::global(300, 0).amp_at(70, &new), 297);
+}
+
+proptest! {
+    // AMP as `mine_hashes` decayed it before the decay moved to `crank_totals`, with a tx in every slot
+    #[test]
+    fn decay_matches_per_tx_decay(
+        amp_cycle_slots in 1u64..50,
+        slots in 1u64..2_000,
+        cranks in proptest::collection::btree_set(1u64..2_000, 0..10),
+    ) {
+        let params = params(amp_cycle_slots);
+        let mut per_tx = global(300, 0);
+        let mut cranked = global(300, 0);
+        for slot in 1..=slots {
+            if slot - per_tx.last_amp_slot > amp_cycle_slots && per_tx.amp > 0 {
+                per_tx.amp -= 1;
+                per_tx.last_amp_slot = slot;
+            }
+            if cranks.contains(&slot) {
+                cranked.store_decay(slot, &params);
+            }
+            prop_assert_eq!(cranked.amp_at(slot, &params), per_tx.amp, "slot {}", slot);
+        }
+    }
+
+    // with gaps between txs, AMP follows the slot clock, never above what per-tx decay left
+    #[test]
+    fn decay_follows_slot_clock_across_gaps(
+        amp_cycle_slots in 1u64..50,
+        txs in proptest::collection::btree_set(1u64..5_000, 1..50),
+        cranks in proptest::collection::btree_set(1u64..5_000, 0..10),
+    ) {
+        let params = params(amp_cycle_slots);
+        let mut per_tx = global(300, 0);
+        let mut cranked = global(300, 0);
+        for slot in 1..5_000u64 {
+            if cranks.contains(&slot) {
+                cranked.store_decay(slot, &params);
+            }
+            if !txs.contains(&slot) {
+                continue;
+            }
+            if slot - per_tx.last_amp_slot > amp_cycle_slots && per_tx.amp > 0 {
+                per_tx.amp -= 1;
+                per_tx.last_amp_slot = slot;
+            }
+            let amp = cranked.amp_at(slot, &params);
+            prop_assert_eq!(amp as u64, 300u64.saturating_sub(slot / (amp_cycle_slots + 1)), "slot {}", slot);
+            prop_assert!(amp <= per_tx.amp, "slot {}", slot);
+        }
+    }
+
+}
@@ -1,10 +1,14 @@
 use anchor_lang::{
     prelude::*,
+    system_program,
+    Discriminator,
 };
 use anchor_spl::{
-    token::{Token, Mint, MintTo, TokenAccount, SetAuthority},
+    token::Token,
+    token_2022::{Token2022, spl_token_2022::instruction::AuthorityType},
+    token_interface::{Mint, MintTo, TokenAccount, TokenInterface, SetAuthority, TokenMetadataInitialize},
+    token_interface::{mint_to, set_authority, token_metadata_initialize, spl_token_metadata_interface, spl_pod},
     metadata::{create_metadata_accounts_v3, CreateMetadataAccountsV3, Metadata, mpl_token_metadata},
-    token::{mint_to, set_authority},
     associated_token::AssociatedToken,
 };
 use mpl_token_metadata::{types::DataV2};
@@ -13,13 +17,79 @@ declare_id!("EPAdVJ5S317jJr2ejgxoA52iptvphGXjPLbqXhZH4n8o");
 
 const START_SLOT: u64 = 1;
 
-// TODO: lock to a specifig admin key
-// const ADMIN_KEY: &str = "somesecretadminkey";
+/// Miner program registered by `init_config` for every kind, the one serving all kinds.
+/// Legacy per-kind miners can't be registered: their points are converted once imported into
+/// the records of this one, which keeps a single `points_counters[kind]` in step with what is minted
+pub const MINERS: [Pubkey; sol_xen_miner::MAX_KINDS as usize] = [sol_xen_miner::ID; sol_xen_miner::MAX_KINDS as usize];
+
+/// Points (9 decimals) per solXEN base unit (6 decimals)
+const POINTS_PER_BASE_UNIT: u128 = 1_000;
+
+/// Max number of (kind, miner program) entries in `MinterConfig`
+pub const MAX_MINER_ENTRIES: usize = 16;
+
+/// Layout version of `UserTokensRecord`, stored right after the original fields.
+/// Version 0 had no version byte and counted converted points in `tokens_minted`
+pub const TOKENS_RECORD_VERSION: u8 = 1;
 
 #[program]
 pub mod sol_xen_minter {
     use super::*;
 
+    pub fn init_config(ctx: Context<InitConfig>) -> Result<()> {
+        // program upgrade authority becomes the first admin
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.pending_admin = Pubkey::default();
+        for (kind, program_id) in MINERS.iter().enumerate() {
+            config.miners.push(MinerEntry { kind: kind as u8, program_id: *program_id, frozen: false });
+        }
+        Ok(())
+    }
+
+    pub fn transfer_admin(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<()> {
+        // two-step handover: new admin has to accept to take over
+        ctx.accounts.config.pending_admin = new_admin;
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        ctx.accounts.config.admin = ctx.accounts.pending_admin.key();
+        ctx.accounts.config.pending_admin = Pubkey::default();
+        Ok(())
+    }
+
+    /// Allows user records of `program_id` to be converted as points of `kind`
+    pub fn add_miner(ctx: Context<UpdateMiners>, kind: u8, program_id: Pubkey) -> Result<()> {
+        require!(kind < sol_xen_miner::MAX_KINDS, SolXenError::BadParam);
+        require!(!is_legacy_miner(&program_id), SolXenError::LegacyMiner);
+        let config = &mut ctx.accounts.config;
+        require!(!config.is_allowed(kind, &program_id), SolXenError::MinerAlreadyRegistered);
+        require!(config.miners.len() < MAX_MINER_ENTRIES, SolXenError::TooManyMiners);
+        config.miners.push(MinerEntry { kind, program_id, frozen: false });
+        emit!(MinerRegistryUpdated { kind, program_id, allowed: true, frozen: false });
+        Ok(())
+    }
+
+    pub fn remove_miner(ctx: Context<UpdateMiners>, kind: u8, program_id: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let index = config.position(kind, &program_id).ok_or(SolXenError::MinerNotRegistered)?;
+        require!(!config.miners[index].frozen, SolXenError::MinerIsFrozen);
+        config.miners.remove(index);
+        emit!(MinerRegistryUpdated { kind, program_id, allowed: false, frozen: false });
+        Ok(())
+    }
+
+    /// Makes the entry permanent: it can't be removed any more, so points mined with `program_id`
+    /// are guaranteed to stay convertible
+    pub fn freeze_miner(ctx: Context<UpdateMiners>, kind: u8, program_id: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let index = config.position(kind, &program_id).ok_or(SolXenError::MinerNotRegistered)?;
+        config.miners[index].frozen = true;
+        emit!(MinerRegistryUpdated { kind, program_id, allowed: true, frozen: true });
+        Ok(())
+    }
+
     pub fn create_mint(ctx: Context<InitTokenMint>, metadata: InitTokenParams) -> Result<()> {
         
         let seeds = &["mint".as_bytes(), &[ctx.bumps.mint_account]];
@@ -60,32 +130,86 @@ pub mod sol_xen_minter {
         Ok(())
     }
 
+    /// Same as `create_mint` for a Token-2022 mint, carrying its metadata in the mint's own metadata extension
+    /// instead of a Metaplex account
+    pub fn create_mint_2022(ctx: Context<InitTokenMint2022>, metadata: InitTokenParams) -> Result<()> {
+        let seeds = &["mint".as_bytes(), &[ctx.bumps.mint_account]];
+        let signer = [&seeds[..]];
+
+        let mint = ctx.accounts.mint_account.to_account_info();
+        let token_metadata = spl_token_metadata_interface::state::TokenMetadata {
+            update_authority: spl_pod::optional_keys::OptionalNonZeroPubkey(ctx.accounts.admin.key()),
+            mint: mint.key(),
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+            additional_metadata: vec![],
+        };
+        // the token program grows the mint by the metadata, but doesn't pay for the extra space
+        let new_len = mint.data_len() + token_metadata.tlv_size_of()?;
+        let rent_due = Rent::get()?.minimum_balance(new_len).saturating_sub(mint.lamports());
+        if rent_due > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: mint.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+
+        token_metadata_initialize(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TokenMetadataInitialize {
+                    token_program_id: ctx.accounts.token_program.to_account_info(),
+                    metadata: mint.clone(),
+                    update_authority: ctx.accounts.admin.to_account_info(),
+                    mint_authority: mint.clone(),
+                    mint,
+                },
+                &signer
+            ),
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+        )
+    }
+
     pub fn revoke_mint_authority(ctx: Context<RevokeMintAuthority>, ) -> Result<()> {
+        // the mint is its own authority, so the mint PDA signs
+        let seeds = &["mint".as_bytes(), &[ctx.bumps.mint_account]];
+        let signer = [&seeds[..]];
+        let mint = ctx.accounts.mint_account.to_account_info();
+
         set_authority(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 SetAuthority {
-                    account_or_mint:ctx.accounts.mint_account.to_account_info(),
-                    current_authority:ctx.accounts.admin.to_account_info(),
+                    account_or_mint: mint.clone(),
+                    current_authority: mint.clone(),
                 },
+                &signer
             ),
-            anchor_spl::token::spl_token::instruction::AuthorityType::FreezeAccount,
+            AuthorityType::FreezeAccount,
             None
-        ).expect("Error relinquishing Mint FreezeAccount");
-        
+        )?;
+
         set_authority(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 SetAuthority {
-                    account_or_mint:ctx.accounts.mint_account.to_account_info(),
-                    current_authority:ctx.accounts.admin.to_account_info(),
+                    account_or_mint: mint.clone(),
+                    current_authority: mint,
                 },
+                &signer
             ),
-            anchor_spl::token::spl_token::instruction::AuthorityType::CloseAccount,
+            AuthorityType::CloseAccount,
             None
-        ).expect("Error relinquishing Mint CloseAccount");
-        
-        Ok(())
+        )
     }
 
     pub fn mint_tokens(ctx: Context<MintTokens>, kind: u8) -> Result<()> {
@@ -93,68 +217,309 @@ pub mod sol_xen_minter {
         let slot = Clock::get().unwrap().slot;
         require!(slot > START_SLOT, SolXenError::MintIsNotActive);
         
-        require!(kind < 4, SolXenError::BadParam);
+        require!(kind < sol_xen_miner::MAX_KINDS, SolXenError::BadParam);
 
-        let comma_delimited = "B8HwMYCk1o7EaJhooM4P43BHSk5M8zZHsTeJixqw7LMN,2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ,5dxcK28nyAJdK9fSFuReRREeKnmAGVRpXPhwkZxAxFtJ,DdVCjv7fsPPm64HnepYy5MBfh2bNfkd84Rawey9rdt5S";
+        let minter_program_key = ctx.accounts.miner_program.key();
+        let points = load_user_record(
+            &ctx.accounts.config,
+            ctx.accounts.user.key,
+            kind,
+            &ctx.accounts.user_record,
+            &minter_program_key,
+        )?.points;
+        let recipient = payout_recipient(ctx.accounts.user.key, &ctx.accounts.payout)?;
+        require_keys_eq!(ctx.accounts.recipient.key(), recipient, SolXenError::BadRecipient);
 
-        let miners: Vec<Pubkey> = comma_delimited
-            .split(',')
-            .map(|s| solana_program::pubkey::Pubkey::try_from(s).unwrap())
-            .collect();
-        
-        require!(kind < 4, SolXenError::BadParam);
-        // require!(ctx.accounts.miner_program.owner == "owner");
+        let user_tokens_record = &mut ctx.accounts.user_tokens_record;
+        // fresh record, older ones go through `migrate_tokens_record`
+        user_tokens_record.version = TOKENS_RECORD_VERSION;
 
-        let minter_program_key = ctx.accounts.miner_program.key();
-        require!(miners[kind as usize] == minter_program_key, SolXenError::BadParam);
-
-        let (user_record_pda, _bump_seed) =
-            Pubkey::find_program_address(&[
-                b"xn-by-sol",
-                ctx.accounts.user.key.as_ref(),
-                &[kind],
-                &minter_program_key.to_bytes()
-            ], &minter_program_key);
-        require!(user_record_pda == ctx.accounts.user_record.key(), SolXenError::BadOwner);
-        require!(*ctx.accounts.user_record.owner == minter_program_key, SolXenError::BadOwner);
-
-        let mut buf: &[u8] = &ctx.accounts.user_record.try_borrow_mut_data()?[..];
-        let user_record: UserSolXnRecord = UserSolXnRecord::try_deserialize(&mut buf)?;
-        let points = user_record.points as u64;
-        print!("Miner kind {}, total points {}", kind, points);
-
-        let current_token_balance = ctx.accounts.user_tokens_record.tokens_minted as u64;
-
-        let token_account_seeds: &[&[&[u8]]] = &[&[b"mint", &[ctx.bumps.mint_account]]];
-        let points_to_mint = if points > ctx.accounts.user_tokens_record.points_counters[kind as usize] as u64
-        { points - ctx.accounts.user_tokens_record.points_counters[kind as usize] as u64 } else
-        { 0 };
-        print!("solXEN balance {}, new tokens to mint {}", current_token_balance, points_to_mint);
+        let points_to_mint = user_tokens_record.points_to_convert(kind, points);
 
         if points_to_mint > 0 {
-            // increment minted counter for user
-            ctx.accounts.user_tokens_record.tokens_minted += points_to_mint as u128;
-            ctx.accounts.user_tokens_record.points_counters[kind as usize] += points_to_mint as u128;
+            let amount = user_tokens_record.convert(kind, points_to_mint);
+            ctx.accounts.supply.record(kind, points_to_mint, amount)?;
+            let amount = u64::try_from(amount).map_err(|_| SolXenError::AmountOverflow)?;
+            emit!(TokensMinted {
+                user: ctx.accounts.user.key(),
+                kind,
+                miner_program: minter_program_key,
+                points,
+                points_minted: points_to_mint,
+                amount,
+                points_counter: user_tokens_record.points_counters[kind as usize],
+                points_converted: user_tokens_record.points_converted,
+                tokens_minted: user_tokens_record.tokens_minted,
+            });
+
             // Mint solXEN tokens to user
-            mint_to(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    MintTo {
-                        mint: ctx.accounts.mint_account.to_account_info(),
-                        authority: ctx.accounts.mint_account.to_account_info(),
-                        to: ctx.accounts.user_token_account.to_account_info(),
-                    },
-                    token_account_seeds
-                ), // using PDA to sign
-                points_to_mint / 1000,
+            mint_to_user(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint_account,
+                &ctx.accounts.user_token_account,
+                ctx.bumps.mint_account,
+                amount,
             )?;
         }
 
         Ok(())
     }
+
+    /// Same as `mint_tokens` for every kind at once: remaining accounts are the user's `xn-by-sol` records
+    /// indexed by kind, each one owned by the miner program it was mined with; kinds not mined can be
+    /// passed as empty (not yet created) accounts
+    pub fn mint_all(ctx: Context<MintAll>) -> Result<()> {
+        let slot = Clock::get().unwrap().slot;
+        require!(slot > START_SLOT, SolXenError::MintIsNotActive);
+        require!(ctx.remaining_accounts.len() <= sol_xen_miner::MAX_KINDS as usize, SolXenError::BadParam);
+        let recipient = payout_recipient(ctx.accounts.user.key, &ctx.accounts.payout)?;
+        require_keys_eq!(ctx.accounts.recipient.key(), recipient, SolXenError::BadRecipient);
+
+        let user_tokens_record = &mut ctx.accounts.user_tokens_record;
+        user_tokens_record.version = TOKENS_RECORD_VERSION;
+
+        let mut amount: u128 = 0;
+        for (kind, user_record) in ctx.remaining_accounts.iter().enumerate() {
+            let kind = kind as u8;
+            if user_record.data_is_empty() {
+                continue;
+            }
+            // validated against the miners registered for `kind` by `load_user_record`
+            let miner_program = *user_record.owner;
+            let points = load_user_record(&ctx.accounts.config, ctx.accounts.user.key, kind, user_record, &miner_program)?
+                .points;
+            let points_to_mint = user_tokens_record.points_to_convert(kind, points);
+            if points_to_mint == 0 {
+                continue;
+            }
+
+            let kind_amount = user_tokens_record.convert(kind, points_to_mint);
+            ctx.accounts.supply.record(kind, points_to_mint, kind_amount)?;
+            amount += kind_amount;
+            emit!(TokensMinted {
+                user: ctx.accounts.user.key(),
+                kind,
+                miner_program,
+                points,
+                points_minted: points_to_mint,
+                amount: u64::try_from(kind_amount).map_err(|_| SolXenError::AmountOverflow)?,
+                points_counter: user_tokens_record.points_counters[kind as usize],
+                points_converted: user_tokens_record.points_converted,
+                tokens_minted: user_tokens_record.tokens_minted,
+            });
+        }
+
+        // Mint solXEN tokens of all kinds to user at once
+        mint_to_user(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint_account,
+            &ctx.accounts.user_token_account,
+            ctx.bumps.mint_account,
+            u64::try_from(amount).map_err(|_| SolXenError::AmountOverflow)?,
+        )
+    }
+
+    /// Sends the user's mints to the token account of `recipient` from now on, instead of their own; can only be set once
+    pub fn set_payout(ctx: Context<SetPayout>, recipient: Pubkey) -> Result<()> {
+        require!(recipient != Pubkey::default(), SolXenError::BadParam);
+        ctx.accounts.payout.recipient = recipient;
+        emit!(PayoutSet { user: ctx.accounts.user.key(), recipient });
+        Ok(())
+    }
+
+    /// Starts tracking the minted supply, from the current supply of the mint; `cap` of 0 means no cap
+    pub fn init_supply(ctx: Context<InitSupply>, cap: u128) -> Result<()> {
+        let supply = &mut ctx.accounts.supply;
+        // minted before the supply record existed
+        supply.tokens_minted = ctx.accounts.mint_account.supply as u128;
+        require!(cap == 0 || cap >= supply.tokens_minted, SolXenError::BadParam);
+        supply.cap = cap;
+        Ok(())
+    }
+
+    /// Sets the cap (base units) on the minted supply; 0 lifts it, otherwise it can't be below what has been minted
+    pub fn set_supply_cap(ctx: Context<SetSupplyCap>, cap: u128) -> Result<()> {
+        let supply = &mut ctx.accounts.supply;
+        require!(cap == 0 || cap >= supply.tokens_minted, SolXenError::BadParam);
+        supply.cap = cap;
+        emit!(SupplyCapUpdated { cap, tokens_minted: supply.tokens_minted });
+        Ok(())
+    }
+
+    /// Read-only view of the minted supply, to be simulated
+    pub fn get_supply_stats(ctx: Context<GetSupplyStats>) -> Result<SupplyStats> {
+        let supply = &ctx.accounts.supply;
+        Ok(SupplyStats {
+            cap: supply.cap,
+            tokens_minted: supply.tokens_minted,
+            remaining: (supply.cap > 0).then(|| supply.cap.saturating_sub(supply.tokens_minted)),
+            points_converted: supply.points_converted,
+            points_counters: supply.points_counters,
+            mint_supply: ctx.accounts.mint_account.supply,
+        })
+    }
+
+    pub fn migrate_tokens_record(ctx: Context<MigrateTokensRecord>, _user: Pubkey) -> Result<()> {
+        let record = ctx.accounts.user_tokens_record.to_account_info();
+        require_keys_eq!(*record.owner, ID, SolXenError::BadOwner);
+        let new_len = 8 + UserTokensRecord::INIT_SPACE;
+        let upgraded = {
+            let data = record.try_borrow_data()?;
+            require!(data.len() > 8 && data[..8] == UserTokensRecord::DISCRIMINATOR, SolXenError::BadOwner);
+            if data.len() == new_len {
+                msg!("Record is up to date");
+                return Ok(());
+            }
+            UserTokensRecord::upgrade(&data[8..]).ok_or(SolXenError::BadOwner)?
+        };
+
+        let rent_due = Rent::get()?.minimum_balance(new_len).saturating_sub(record.lamports());
+        if rent_due > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: record.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        record.realloc(new_len, false)?;
+        upgraded.try_serialize(&mut &mut record.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+}
+
+/// The user's `xn-by-sol` record of `kind`, checked to be the PDA of `miner_program`,
+/// one of the miners registered in `config` for that kind (never a legacy one), and to be owned by it
+pub fn load_user_record(
+    config: &MinterConfig,
+    user: &Pubkey,
+    kind: u8,
+    user_record: &AccountInfo,
+    miner_program: &Pubkey,
+) -> Result<sol_xen_miner::UserSolXnRecord> {
+    require!(kind < sol_xen_miner::MAX_KINDS, SolXenError::BadParam);
+    require!(!is_legacy_miner(miner_program), SolXenError::LegacyMiner);
+    require!(config.is_allowed(kind, miner_program), SolXenError::MinerNotRegistered);
+
+    let (user_record_pda, _bump_seed) =
+        Pubkey::find_program_address(&[
+            b"xn-by-sol",
+            user.as_ref(),
+            &[kind],
+            &miner_program.to_bytes()
+        ], miner_program);
+    require!(user_record_pda == user_record.key(), SolXenError::BadOwner);
+
+    require!(user_record.owner == miner_program, SolXenError::BadOwner);
+    let data = user_record.try_borrow_data()?;
+    if *miner_program == sol_xen_miner::UserSolXnRecord::owner() {
+        // typed: checks the discriminator and the (current) layout
+        return sol_xen_miner::UserSolXnRecord::try_deserialize(&mut &data[..]);
+    }
+    // miners registered later may keep older record layouts
+    sol_xen_miner::read_record::<sol_xen_miner::UserSolXnRecord>(&data).ok_or(error!(SolXenError::BadOwner))
+}
+
+/// One of the legacy per-kind miners, replaced by the miner serving all kinds
+pub fn is_legacy_miner(program_id: &Pubkey) -> bool {
+    sol_xen_miner::LEGACY_MINERS.contains(program_id)
+}
+
+/// Owner of the token account the user's mints go to: the recipient of their `PayoutRecord` if they have one, else the user
+pub fn payout_recipient(user: &Pubkey, payout: &AccountInfo) -> Result<Pubkey> {
+    if payout.data_is_empty() {
+        return Ok(*user);
+    }
+    require_keys_eq!(*payout.owner, ID, SolXenError::BadOwner);
+    let data = payout.try_borrow_data()?;
+    Ok(PayoutRecord::try_deserialize(&mut &data[..])?.recipient)
+}
+
+fn mint_to_user<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint_account: &InterfaceAccount<'info, Mint>,
+    user_token_account: &InterfaceAccount<'info, TokenAccount>,
+    mint_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let token_account_seeds: &[&[&[u8]]] = &[&[b"mint", &[mint_bump]]];
+    mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo {
+                mint: mint_account.to_account_info(),
+                authority: mint_account.to_account_info(),
+                to: user_token_account.to_account_info(),
+            },
+            token_account_seeds
+        ), // using PDA to sign
+        amount,
+    )
+}
+
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        space = 8 + MinterConfig::INIT_SPACE,
+        seeds = [b"xn-minter-config"],
+        bump,
+        payer = admin,
+    )]
+    pub config: Box<Account<'info, MinterConfig>>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, program::SolXenMinter>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ SolXenError::NotAdmin)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAdmin<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"xn-minter-config"],
+        bump,
+        has_one = admin @ SolXenError::NotAdmin,
+    )]
+    pub config: Box<Account<'info, MinterConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"xn-minter-config"],
+        bump,
+        has_one = pending_admin @ SolXenError::NotPendingAdmin,
+    )]
+    pub config: Box<Account<'info, MinterConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMiners<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"xn-minter-config"],
+        bump,
+        has_one = admin @ SolXenError::NotAdmin,
+    )]
+    pub config: Box<Account<'info, MinterConfig>>,
 }
 
-// TODO 1: add checks to lock this method to a specific (admin) Key
+// DONE 1: add checks to lock this method to a specific (admin) Key
 // TODO 2: after the Token Mint is launched, remove authority from it (First Principles)
 // DONE 3: add metadata support (https://github.com/solana-developers/program-examples/blob/main/tokens/pda-mint-authority/anchor/programs/token-minter/src/instructions/create.rs)
 
@@ -163,6 +528,12 @@ pub mod sol_xen_minter {
 pub struct InitTokenMint<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"xn-minter-config"],
+        bump,
+        has_one = admin @ SolXenError::NotAdmin,
+    )]
+    pub config: Box<Account<'info, MinterConfig>>,
     #[account(
         init_if_needed,
         seeds = [b"mint"],
@@ -170,11 +541,21 @@ pub struct InitTokenMint<'info> {
         payer = admin,
         mint::decimals = params.decimals,
         mint::authority = mint_account.key(),
+        mint::token_program = token_program,
         // mint::freeze_authority = admin.key(),
     )]
-    pub mint_account: Box<Account<'info, Mint>>,
-    /// CHECK: Address validated using constraint
-    #[account(mut)]
+    pub mint_account: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: Address validated using constraint, created by the token metadata program
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            mint_account.key().as_ref()
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
     pub metadata: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token>,
     pub token_metadata_program: Program<'info, Metadata>,
@@ -183,12 +564,45 @@ pub struct InitTokenMint<'info> {
 }
 
 #[derive(Accounts)]
-pub struct RevokeMintAuthority<'info> {
+#[instruction(params: InitTokenParams)]
+pub struct InitTokenMint2022<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"xn-minter-config"],
+        bump,
+        has_one = admin @ SolXenError::NotAdmin,
+    )]
+    pub config: Box<Account<'info, MinterConfig>>,
+    #[account(
+        init,
+        seeds = [b"mint"],
+        bump,
+        payer = admin,
+        mint::decimals = params.decimals,
+        mint::authority = mint_account.key(),
+        mint::token_program = token_program,
+        extensions::metadata_pointer::authority = admin.key(),
+        extensions::metadata_pointer::metadata_address = mint_account.key(),
+    )]
+    pub mint_account: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeMintAuthority<'info> {
     #[account(mut)]
-    pub mint_account: Box<Account<'info, Mint>>,
-    pub token_program: Program<'info, Token>
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"xn-minter-config"],
+        bump,
+        has_one = admin @ SolXenError::NotAdmin,
+    )]
+    pub config: Box<Account<'info, MinterConfig>>,
+    #[account(mut, seeds = [b"mint"], bump, mint::token_program = token_program)]
+    pub mint_account: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
@@ -202,8 +616,8 @@ pub struct InitTokenParams {
 #[derive(Accounts)]
 #[instruction(kind: u8)]
 pub struct MintTokens<'info> {
-    /// CHECK: Address validated using PDA address derivation from seeds
-    pub user_record: AccountInfo<'info>,
+    /// CHECK: address, owner and layout checked by `load_user_record`
+    pub user_record: UncheckedAccount<'info>,
     #[account(
         init_if_needed,
         seeds = [
@@ -219,34 +633,306 @@ pub struct MintTokens<'info> {
         init_if_needed,
         payer = user,
         associated_token::mint = mint_account,
-        associated_token::authority = user,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program,
     )]
-    pub user_token_account: Box<Account<'info, TokenAccount>>,
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(mut)]
     pub user: Signer<'info>,
-    #[account(mut, seeds = [b"mint"], bump)]
-    pub mint_account: Account<'info, Mint>,
-    pub token_program: Program<'info, Token>,
+    #[account(mut, seeds = [b"mint"], bump, mint::token_program = token_program)]
+    pub mint_account: Box<InterfaceAccount<'info, Mint>>,
+    /// SPL Token or Token-2022, whichever the mint was created with
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    /// CHECK: Address validated using PDA address derivation from seeds
-    pub miner_program: AccountInfo<'info>,
+    /// CHECK: checked against `config` by `load_user_record`
+    pub miner_program: UncheckedAccount<'info>,
+    #[account(seeds = [b"xn-minter-config"], bump)]
+    pub config: Box<Account<'info, MinterConfig>>,
+    #[account(mut, seeds = [b"xn-minter-supply"], bump)]
+    pub supply: Box<Account<'info, SupplyRecord>>,
+    /// CHECK: owner of `user_token_account`, the user or their payout recipient; checked against `payout`
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: the user's `PayoutRecord`, if they've set one; read by `payout_recipient`
+    #[account(seeds = [b"xn-payout", user.key().as_ref()], bump)]
+    pub payout: UncheckedAccount<'info>,
     // pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct MintAll<'info> {
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"sol-xen-minted",
+            user.key().as_ref()
+        ],
+        payer = user,
+        space = 8 + UserTokensRecord::INIT_SPACE,
+        bump
+    )]
+    pub user_tokens_record: Box<Account<'info, UserTokensRecord>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_account,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program,
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut, seeds = [b"mint"], bump, mint::token_program = token_program)]
+    pub mint_account: Box<InterfaceAccount<'info, Mint>>,
+    /// SPL Token or Token-2022, whichever the mint was created with
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    #[account(seeds = [b"xn-minter-config"], bump)]
+    pub config: Box<Account<'info, MinterConfig>>,
+    #[account(mut, seeds = [b"xn-minter-supply"], bump)]
+    pub supply: Box<Account<'info, SupplyRecord>>,
+    /// CHECK: owner of `user_token_account`, the user or their payout recipient; checked against `payout`
+    pub recipient: UncheckedAccount<'info>,
+    /// CHECK: the user's `PayoutRecord`, if they've set one; read by `payout_recipient`
+    #[account(seeds = [b"xn-payout", user.key().as_ref()], bump)]
+    pub payout: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPayout<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init,
+        space = 8 + PayoutRecord::INIT_SPACE,
+        seeds = [b"xn-payout", user.key().as_ref()],
+        bump,
+        payer = user,
+    )]
+    pub payout: Box<Account<'info, PayoutRecord>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitSupply<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"xn-minter-config"],
+        bump,
+        has_one = admin @ SolXenError::NotAdmin,
+    )]
+    pub config: Box<Account<'info, MinterConfig>>,
+    #[account(
+        init,
+        space = 8 + SupplyRecord::INIT_SPACE,
+        seeds = [b"xn-minter-supply"],
+        bump,
+        payer = admin,
+    )]
+    pub supply: Box<Account<'info, SupplyRecord>>,
+    #[account(seeds = [b"mint"], bump)]
+    pub mint_account: Box<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSupplyCap<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"xn-minter-config"],
+        bump,
+        has_one = admin @ SolXenError::NotAdmin,
+    )]
+    pub config: Box<Account<'info, MinterConfig>>,
+    #[account(mut, seeds = [b"xn-minter-supply"], bump)]
+    pub supply: Box<Account<'info, SupplyRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct GetSupplyStats<'info> {
+    #[account(seeds = [b"xn-minter-supply"], bump)]
+    pub supply: Box<Account<'info, SupplyRecord>>,
+    #[account(seeds = [b"mint"], bump)]
+    pub mint_account: Box<InterfaceAccount<'info, Mint>>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct MigrateTokensRecord<'info> {
+    /// CHECK: legacy layout can't be deserialized; address checked by seeds, owner and discriminator by the handler
+    #[account(
+        mut,
+        seeds = [
+            b"sol-xen-minted",
+            user.as_ref()
+        ],
+        bump
+    )]
+    pub user_tokens_record: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
-#[derive(Debug)]
-pub struct UserSolXnRecord {
-    pub hashes: u64,
-    pub superhashes: u32,
-    pub points: u128
+#[derive(InitSpace, Debug)]
+pub struct MinterConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    /// Miner programs whose user records can be converted
+    #[max_len(MAX_MINER_ENTRIES)]
+    pub miners: Vec<MinerEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq)]
+pub struct MinerEntry {
+    pub kind: u8,
+    pub program_id: Pubkey,
+    /// Frozen entries can't be removed
+    pub frozen: bool,
 }
 
+impl MinterConfig {
+    fn position(&self, kind: u8, program_id: &Pubkey) -> Option<usize> {
+        self.miners.iter().position(|entry| entry.kind == kind && entry.program_id == *program_id)
+    }
+
+    /// True if user records of `program_id` can be converted as points of `kind`
+    pub fn is_allowed(&self, kind: u8, program_id: &Pubkey) -> bool {
+        self.position(kind, program_id).is_some()
+    }
+}
+
+// `points_counters` has to stay first: `sol_xen_miner::minted_points` reads it at a fixed offset
 #[account]
 #[derive(InitSpace, Default)]
 pub struct UserTokensRecord {
     pub points_counters: [u128; 4],
-    pub tokens_minted: u128
+    /// solXEN minted, in token base units
+    pub tokens_minted: u128,
+    pub version: u8,
+    /// points converted so far, across all kinds
+    pub points_converted: u128,
+    /// converted points short of a whole base unit, carried over to the next mint
+    pub points_remainder: u16,
+}
+
+impl UserTokensRecord {
+    /// Points of `kind` not converted yet out of the `points` of the user's record
+    pub fn points_to_convert(&self, kind: u8, points: u128) -> u128 {
+        points.saturating_sub(self.points_counters[kind as usize])
+    }
+
+    /// Accounts for `points` of `kind` converted; returns the base units to mint for them.
+    /// Points short of a whole base unit are carried over to the next conversion instead of being dropped
+    pub fn convert(&mut self, kind: u8, points: u128) -> u128 {
+        let convertible = points + self.points_remainder as u128;
+        let amount = convertible / POINTS_PER_BASE_UNIT;
+        self.points_remainder = (convertible % POINTS_PER_BASE_UNIT) as u16;
+        self.points_counters[kind as usize] += points;
+        self.points_converted += points;
+        self.tokens_minted += amount;
+        amount
+    }
+
+    /// Record in the current layout from the data (past the discriminator) of version 0.
+    /// Version 0 minted `points / 1000` on every mint and dropped the rest; the dropped remainders can't be
+    /// told apart any more, so `tokens_minted` is taken as `points_converted / 1000`, an upper bound on what was minted.
+    /// Those remainders are gone: the record starts with none to carry over
+    pub fn upgrade(data: &[u8]) -> Option<Self> {
+        if data.len() != 80 {
+            return None;
+        }
+        let (points_counters, points_converted) = <([u128; 4], u128)>::try_from_slice(data).ok()?;
+        Some(UserTokensRecord {
+            points_counters,
+            tokens_minted: points_converted / POINTS_PER_BASE_UNIT,
+            version: TOKENS_RECORD_VERSION,
+            points_converted,
+            points_remainder: 0,
+        })
+    }
+}
+
+/// Owner of the token account the user's mints go to, see `set_payout`
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct PayoutRecord {
+    pub recipient: Pubkey,
+}
+
+/// Minted supply across all users, in token base units; `points_converted` and `points_counters` only count
+/// mints since `init_supply`, `tokens_minted` includes the supply of the mint at that time
+#[account]
+#[derive(InitSpace, Default, Debug)]
+pub struct SupplyRecord {
+    /// 0 means no cap
+    pub cap: u128,
+    pub tokens_minted: u128,
+    pub points_converted: u128,
+    pub points_counters: [u128; 4],
+}
+
+impl SupplyRecord {
+    /// Accounts for `amount` base units minted for `points` of `kind`, failing if that would go over the cap
+    pub fn record(&mut self, kind: u8, points: u128, amount: u128) -> Result<()> {
+        let tokens_minted = self.tokens_minted + amount;
+        require!(self.cap == 0 || tokens_minted <= self.cap, SolXenError::SupplyCapReached);
+        self.tokens_minted = tokens_minted;
+        self.points_converted += points;
+        self.points_counters[kind as usize] += points;
+        Ok(())
+    }
+}
+
+/// Return data of `get_supply_stats`; `remaining` is `None` when there is no cap.
+/// `mint_supply` is the supply of the mint itself
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SupplyStats {
+    pub cap: u128,
+    pub tokens_minted: u128,
+    pub remaining: Option<u128>,
+    pub points_converted: u128,
+    pub points_counters: [u128; 4],
+    pub mint_supply: u64,
+}
+
+/// `amount` and `tokens_minted` are in token base units; `points_counter`, `points_converted` and `tokens_minted`
+/// are the user's totals after the tx
+#[event]
+pub struct TokensMinted {
+    user: Pubkey,
+    kind: u8,
+    miner_program: Pubkey,
+    points: u128,
+    points_minted: u128,
+    amount: u64,
+    points_counter: u128,
+    points_converted: u128,
+    tokens_minted: u128,
+}
+
+/// Registry entry of `kind` and `program_id` added (`allowed`), removed or frozen
+#[event]
+pub struct MinerRegistryUpdated {
+    kind: u8,
+    program_id: Pubkey,
+    allowed: bool,
+    frozen: bool,
+}
+
+#[event]
+pub struct PayoutSet {
+    user: Pubkey,
+    recipient: Pubkey,
+}
+
+#[event]
+pub struct SupplyCapUpdated {
+    cap: u128,
+    tokens_minted: u128,
 }
 
 #[error_code]
@@ -261,6 +947,26 @@ pub enum SolXenError {
     BadOwner,
     #[msg("Bad param value")]
     BadParam,
+    #[msg("Signer is not the minter admin")]
+    NotAdmin,
+    #[msg("Signer is not the pending minter admin")]
+    NotPendingAdmin,
+    #[msg("Miner program is not registered for this kind")]
+    MinerNotRegistered,
+    #[msg("Miner program is already registered for this kind")]
+    MinerAlreadyRegistered,
+    #[msg("Miner registry is full")]
+    TooManyMiners,
+    #[msg("Miner registry entry is frozen")]
+    MinerIsFrozen,
+    #[msg("solXEN supply cap reached")]
+    SupplyCapReached,
+    #[msg("Token account owner is not the user's payout recipient")]
+    BadRecipient,
+    #[msg("Legacy miner records have to be imported into the miner first")]
+    LegacyMiner,
+    #[msg("Token amount to mint overflows")]
+    AmountOverflow,
 }
 
 
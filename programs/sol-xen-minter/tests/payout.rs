@@ -0,0 +1,35 @@
+use anchor_lang::{prelude::*, AccountSerialize, Space};
+use sol_xen_minter::{payout_recipient, PayoutRecord};
+
+fn recipient_of(user: &Pubkey, owner: &Pubkey, mut data: Vec<u8>) -> Result<Pubkey> {
+    let key = Pubkey::find_program_address(&[b"xn-payout", user.as_ref()], &sol_xen_minter::ID).0;
+    let mut lamports = 1;
+    let payout = AccountInfo::new(&key, false, false, &mut lamports, &mut data, owner, false, 0);
+    payout_recipient(user, &payout)
+}
+
+fn payout_data(recipient: Pubkey) -> Vec<u8> {
+    let mut data = vec![0u8; 8 + PayoutRecord::INIT_SPACE];
+    PayoutRecord { recipient }.try_serialize(&mut &mut data[..]).unwrap();
+    data
+}
+
+#[test]
+fn mints_to_the_user_without_payout() {
+    let user = Pubkey::new_unique();
+    assert_eq!(recipient_of(&user, &System::id(), vec![]).unwrap(), user);
+}
+
+#[test]
+fn mints_to_the_payout_recipient() {
+    let user = Pubkey::new_unique();
+    let cold_wallet = Pubkey::new_unique();
+    assert_eq!(recipient_of(&user, &sol_xen_minter::ID, payout_data(cold_wallet)).unwrap(), cold_wallet);
+
+    // not written by the minter
+    assert!(recipient_of(&user, &Pubkey::new_unique(), payout_data(cold_wallet)).is_err());
+    // other account type of the minter
+    let mut data = payout_data(cold_wallet);
+    data[0] ^= 1;
+    assert!(recipient_of(&user, &sol_xen_minter::ID, data).is_err());
+}
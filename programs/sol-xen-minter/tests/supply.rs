@@ -0,0 +1,39 @@
+use sol_xen_minter::{SupplyRecord, UserTokensRecord};
+
+#[test]
+fn tracks_minted_supply() {
+    let mut supply = SupplyRecord { tokens_minted: 10, ..Default::default() };
+    supply.record(0, 2_000, 2).unwrap();
+    supply.record(3, 1_500, 1).unwrap();
+    assert_eq!((supply.tokens_minted, supply.points_converted), (13, 3_500));
+    assert_eq!(supply.points_counters, [2_000, 0, 0, 1_500]);
+}
+
+#[test]
+fn enforces_the_cap() {
+    let mut supply = SupplyRecord { cap: 5, tokens_minted: 3, ..Default::default() };
+    supply.record(1, 2_000, 2).unwrap();
+    assert_eq!(supply.tokens_minted, 5);
+
+    // a mint going over the cap fails as a whole, and leaves the record untouched
+    assert!(supply.record(1, 1_000, 1).is_err());
+    assert_eq!((supply.tokens_minted, supply.points_converted), (5, 2_000));
+    // nothing to mint is always fine
+    supply.record(1, 999, 0).unwrap();
+
+    supply.cap = 0;
+    supply.record(1, 1_000_000, 1_000).unwrap();
+    assert_eq!(supply.tokens_minted, 1_005);
+}
+
+#[test]
+fn matches_user_records() {
+    let mut supply = SupplyRecord::default();
+    let mut users = [UserTokensRecord::default(), UserTokensRecord::default()];
+    for (user, kind, points) in [(0, 0, 1_999u128), (1, 2, 500), (0, 1, 1), (1, 2, 500)] {
+        let amount = users[user].convert(kind, points);
+        supply.record(kind, points, amount).unwrap();
+    }
+    assert_eq!(supply.tokens_minted, users.iter().map(|user| user.tokens_minted).sum::<u128>());
+    assert_eq!(supply.points_converted, users.iter().map(|user| user.points_converted).sum::<u128>());
+}
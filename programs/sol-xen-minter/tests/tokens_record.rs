@@ -0,0 +1,60 @@
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorSerialize, Space};
+use proptest::prelude::*;
+use sol_xen_minter::{UserTokensRecord, TOKENS_RECORD_VERSION};
+
+#[test]
+fn remainders_are_carried_over() {
+    let mut record = UserTokensRecord::default();
+    // 1999 + 1 points: 1 base unit right away, the second one once the remainder adds up
+    assert_eq!(record.convert(0, 1_999), 1);
+    assert_eq!(record.points_remainder, 999);
+    assert_eq!(record.convert(1, 1), 1);
+    assert_eq!(record.points_remainder, 0);
+    assert_eq!((record.tokens_minted, record.points_converted), (2, 2_000));
+    assert_eq!(record.points_counters, [1_999, 1, 0, 0]);
+}
+
+#[test]
+fn converts_points_past_the_counter() {
+    let mut record = UserTokensRecord::default();
+    record.convert(2, 5_000);
+    // u128 all the way, above u64::MAX
+    let points = u64::MAX as u128 + 5_001;
+    assert_eq!(record.points_to_convert(2, points), u64::MAX as u128 + 1);
+    assert_eq!(record.points_to_convert(1, points), points);
+    // records behind the counter have nothing to convert
+    assert_eq!(record.points_to_convert(2, 4_000), 0);
+}
+
+#[test]
+fn upgrades_version_0() {
+    let legacy = ([5_000u128, 0, 2_500, 1], 7_501u128).try_to_vec().unwrap();
+    let upgraded = UserTokensRecord::upgrade(&legacy).unwrap();
+    assert_eq!(upgraded.version, TOKENS_RECORD_VERSION);
+    assert_eq!(upgraded.points_counters, [5_000, 0, 2_500, 1]);
+    // remainders version 0 dropped aren't carried over
+    assert_eq!((upgraded.points_converted, upgraded.tokens_minted, upgraded.points_remainder), (7_501, 7, 0));
+
+    // what `migrate_tokens_record` writes into the realloc'ed account
+    let mut data = vec![0u8; 8 + UserTokensRecord::INIT_SPACE];
+    upgraded.try_serialize(&mut &mut data[..]).unwrap();
+    let record = UserTokensRecord::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(record.points_converted, 7_501);
+
+    assert!(UserTokensRecord::upgrade(&data[8..]).is_none());
+}
+
+proptest! {
+    #[test]
+    fn never_mints_more_than_converted(mints in prop::collection::vec((0u8..4, 0u128..10_000_000), 1..50)) {
+        let mut record = UserTokensRecord::default();
+        let mut minted = 0;
+        for (kind, points) in mints {
+            minted += record.convert(kind, points);
+        }
+        prop_assert_eq!(minted, record.tokens_minted);
+        prop_assert_eq!(minted, record.points_converted / 1_000);
+        prop_assert_eq!(record.points_remainder as u128, record.points_converted % 1_000);
+        prop_assert_eq!(record.points_counters.iter().sum::<u128>(), record.points_converted);
+    }
+}
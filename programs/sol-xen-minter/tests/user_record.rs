@@ -0,0 +1,110 @@
+use anchor_lang::{prelude::*, Discriminator};
+use sol_xen_miner::{UserSolXnRecord, LEGACY_MINERS, RECORD_VERSION};
+use sol_xen_minter::{is_legacy_miner, load_user_record, MinerEntry, MinterConfig, MINERS};
+
+fn user_record_address(user: &Pubkey, kind: u8, miner_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"xn-by-sol", user.as_ref(), &[kind], miner_program.as_ref()],
+        miner_program,
+    ).0
+}
+
+fn record_data(fields: impl AnchorSerialize) -> Vec<u8> {
+    [UserSolXnRecord::DISCRIMINATOR.as_slice(), &fields.try_to_vec().unwrap()].concat()
+}
+
+// registry as set up by `init_config`
+fn initial_config() -> MinterConfig {
+    let miners = MINERS
+        .iter()
+        .enumerate()
+        .map(|(kind, program_id)| MinerEntry { kind: kind as u8, program_id: *program_id, frozen: false })
+        .collect();
+    MinterConfig { admin: Pubkey::new_unique(), pending_admin: Pubkey::default(), miners }
+}
+
+// loads `data` stored at the record address of `miner_program`, owned by `owner`
+fn load_with(
+    config: &MinterConfig,
+    kind: u8,
+    miner_program: &Pubkey,
+    owner: &Pubkey,
+    mut data: Vec<u8>,
+) -> Result<UserSolXnRecord> {
+    let user = Pubkey::new_from_array([7; 32]);
+    let key = user_record_address(&user, kind, miner_program);
+    let mut lamports = 1;
+    let user_record = AccountInfo::new(&key, false, false, &mut lamports, &mut data, owner, false, 0);
+    load_user_record(config, &user, kind, &user_record, miner_program)
+}
+
+fn load(kind: u8, miner_program: &Pubkey, owner: &Pubkey, data: Vec<u8>) -> Result<UserSolXnRecord> {
+    load_with(&initial_config(), kind, miner_program, owner, data)
+}
+
+#[test]
+fn loads_records_of_allowed_miners() {
+    let current = record_data((RECORD_VERSION, 7u64, 1u64, 42u128));
+    assert_eq!(load(2, &sol_xen_miner::ID, &sol_xen_miner::ID, current).unwrap().points, 42);
+
+    // miners registered later may keep older layouts
+    let older = record_data((7u64, 1u32, 42u128));
+    let new_miner = Pubkey::new_unique();
+    let mut config = initial_config();
+    config.miners.push(MinerEntry { kind: 1, program_id: new_miner, frozen: false });
+    assert_eq!(load_with(&config, 1, &new_miner, &new_miner, older).unwrap().points, 42);
+}
+
+#[test]
+fn rejects_legacy_records() {
+    // legacy points are minted once imported into the miner's record, never from the legacy one
+    let legacy = record_data((7u64, 1u32, 42u128));
+    let mut config = initial_config();
+    assert!(load_with(&config, 1, &LEGACY_MINERS[1], &LEGACY_MINERS[1], legacy.clone()).is_err());
+
+    // not even if registered
+    config.miners.push(MinerEntry { kind: 1, program_id: LEGACY_MINERS[1], frozen: false });
+    assert!(load_with(&config, 1, &LEGACY_MINERS[1], &LEGACY_MINERS[1], legacy).is_err());
+    assert!(is_legacy_miner(&LEGACY_MINERS[0]));
+    assert!(is_legacy_miner(&LEGACY_MINERS[1]));
+    assert!(!is_legacy_miner(&sol_xen_miner::ID));
+}
+
+#[test]
+fn rejects_forged_records() {
+    let current = record_data((RECORD_VERSION, 7u64, 1u64, 42u128));
+    let stranger = Pubkey::new_unique();
+
+    // record address of an allowed miner, but owned by another program
+    assert!(load(0, &sol_xen_miner::ID, &stranger, current.clone()).is_err());
+    assert!(load(1, &LEGACY_MINERS[1], &stranger, current.clone()).is_err());
+    // record of a program which is not a miner
+    assert!(load(0, &stranger, &stranger, current.clone()).is_err());
+    // legacy miner of another kind
+    assert!(load(1, &LEGACY_MINERS[2], &LEGACY_MINERS[2], current.clone()).is_err());
+    // no such kind
+    assert!(load(4, &sol_xen_miner::ID, &sol_xen_miner::ID, current.clone()).is_err());
+
+    // other account type of the miner
+    let mut eth_record = current.clone();
+    eth_record[..8].copy_from_slice(&sol_xen_miner::UserEthXnRecord::DISCRIMINATOR);
+    assert!(load(0, &sol_xen_miner::ID, &sol_xen_miner::ID, eth_record).is_err());
+}
+
+#[test]
+fn follows_the_registry() {
+    let current = record_data((RECORD_VERSION, 7u64, 1u64, 42u128));
+    let new_miner = Pubkey::new_unique();
+    let mut config = initial_config();
+    assert!(config.is_allowed(0, &sol_xen_miner::ID));
+    assert!(!config.is_allowed(0, &new_miner));
+    assert!(load_with(&config, 0, &new_miner, &new_miner, current.clone()).is_err());
+
+    config.miners.push(MinerEntry { kind: 0, program_id: new_miner, frozen: false });
+    assert_eq!(load_with(&config, 0, &new_miner, &new_miner, current.clone()).unwrap().points, 42);
+    // registered for kind 0 only
+    assert!(load_with(&config, 1, &new_miner, &new_miner, current.clone()).is_err());
+
+    config.miners.retain(|entry| entry.program_id != sol_xen_miner::ID);
+    assert!(load_with(&config, 0, &sol_xen_miner::ID, &sol_xen_miner::ID, current).is_err());
+}
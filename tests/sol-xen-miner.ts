@@ -0,0 +1,297 @@
+import * as anchor from "@coral-xyz/anchor";
+import {BN, Program, web3} from "@coral-xyz/anchor";
+import {SolXenMiner} from "../target/types/sol_xen_miner";
+import {expect} from "chai";
+import NodeWallet from "@coral-xyz/anchor/dist/cjs/nodewallet";
+
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+
+const minerParams = {
+  maxHashes: 72,
+  hashPattern: '420',
+  superhashPattern: '42069',
+  superhashX: 250,
+  ampStart: 300,
+  ampCycleSlots: new BN(100_000),
+  startSlot: new BN(0),
+  endSlot: new BN(0),
+  mixSlotHash: false,
+};
+
+describe("sol-xen-miner admin", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.SolXenMiner as Program<SolXenMiner>;
+  const provider = anchor.getProvider();
+  const admin = NodeWallet.local().publicKey;
+  const stranger = web3.Keypair.generate();
+
+  const [programData] = web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+  );
+  const [config] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("xn-miner-config")],
+      program.programId
+  );
+  // shard of the admin's mine_hashes txs, see `shard_index`
+  const [xnShard] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("xn-miner-shard"), Buffer.from([0]), Buffer.from([admin.toBytes()[0] % 16])],
+      program.programId
+  );
+
+  before(async () => {
+    const sig = await provider.connection.requestAirdrop(stranger.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig);
+  });
+
+  it("Config can not be initialized by a non upgrade authority", async () => {
+    try {
+      await program.methods.initConfig()
+          .accounts({admin: stranger.publicKey, programData})
+          .signers([stranger])
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotAdmin');
+    }
+  });
+
+  it("Config can be initialized by the upgrade authority", async () => {
+    await program.methods.initConfig().accounts({admin, programData}).rpc();
+    const configAccount = await program.account.minerConfig.fetch(config);
+    expect(configAccount.admin.toBase58()).to.equal(admin.toBase58());
+  });
+
+  it("Miner can not be initialized by a non-admin", async () => {
+    try {
+      await program.methods.initMiner(0, minerParams)
+          .accounts({admin: stranger.publicKey})
+          .signers([stranger])
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotAdmin');
+    }
+  });
+
+  it("Miner can be initialized by the admin", async () => {
+    await program.methods.initMiner(0, minerParams).accounts({admin}).rpc();
+  });
+
+  it("Admin can be transferred only after the new admin accepts", async () => {
+    await program.methods.transferAdmin(stranger.publicKey).accounts({admin}).rpc();
+    let configAccount = await program.account.minerConfig.fetch(config);
+    expect(configAccount.admin.toBase58()).to.equal(admin.toBase58());
+
+    try {
+      await program.methods.acceptAdmin().accounts({pendingAdmin: admin}).rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotPendingAdmin');
+    }
+
+    await program.methods.acceptAdmin()
+        .accounts({pendingAdmin: stranger.publicKey})
+        .signers([stranger])
+        .rpc();
+    configAccount = await program.account.minerConfig.fetch(config);
+    expect(configAccount.admin.toBase58()).to.equal(stranger.publicKey.toBase58());
+
+    // hand it back
+    await program.methods.transferAdmin(admin)
+        .accounts({admin: stranger.publicKey})
+        .signers([stranger])
+        .rpc();
+    await program.methods.acceptAdmin().accounts({pendingAdmin: admin}).rpc();
+  });
+
+  it("Params can not be updated by a non-admin", async () => {
+    try {
+      await program.methods.updateParams(0, {...minerParams, superhashX: 500})
+          .accounts({admin: stranger.publicKey})
+          .signers([stranger])
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotAdmin');
+    }
+  });
+
+  it("Params out of bounds are rejected", async () => {
+    const badParams = [
+      {...minerParams, maxHashes: 0},
+      {...minerParams, hashPattern: ''},
+      {...minerParams, hashPattern: '42G'},
+      {...minerParams, superhashPattern: '42'},
+      {...minerParams, superhashX: 0},
+      {...minerParams, ampCycleSlots: new BN(0)},
+      {...minerParams, startSlot: new BN(100), endSlot: new BN(50)},
+    ];
+    for (const params of badParams) {
+      try {
+        await program.methods.updateParams(0, params).accounts({admin}).rpc();
+        expect.fail('should have failed');
+      } catch (e) {
+        expect(e.error.errorCode.code).to.equal('InvalidMinerParams');
+      }
+    }
+  });
+
+  it("Params can be updated by the admin", async () => {
+    await program.methods.updateParams(0, {...minerParams, superhashX: 500}).accounts({admin}).rpc();
+    const [params] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("xn-miner-params"), Buffer.from([0])],
+        program.programId
+    );
+    const paramsAccount = await program.account.minerParams.fetch(params);
+    expect(paramsAccount.superhashX).to.equal(500);
+    await program.methods.updateParams(0, minerParams).accounts({admin}).rpc();
+  });
+
+  it("Mining fits well under the default compute unit limit", async () => {
+    const ethAccount = {address: Array(20).fill(2), addressStr: '0x' + '02'.repeat(20)};
+    const tx = await program.methods.mineHashes(ethAccount.address, 0, 72, ethAccount.addressStr)
+        .accounts({user: admin, xnShard})
+        .preInstructions([web3.ComputeBudgetProgram.setComputeUnitLimit({units: 1_400_000})])
+        .transaction();
+    tx.feePayer = admin;
+    tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+    const {value} = await provider.connection.simulateTransaction(tx);
+    expect(value.err).to.equal(null);
+    console.log('mine_hashes compute units:', value.unitsConsumed);
+    // the default build leaves the per-hit and per-tx mining logs to `verbose-logs` builds
+    expect(value.logs.some(log => log.includes('Mined hashes'))).to.equal(false);
+    // 100_000 + 15_000 * iterations, as the clients derive --units
+    expect(value.unitsConsumed).to.be.lessThan(1_180_000);
+  });
+
+  it("Mining with the raw address credits the same records for less compute", async () => {
+    const ethAccount = {address: Array(20).fill(6), addressStr: '0x' + '06'.repeat(20)};
+    const [xnByEth] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("xn-by-eth"), Buffer.from(ethAccount.address), Buffer.from([0]), program.programId.toBuffer()],
+        program.programId
+    );
+    await program.methods.mineHashesV2(ethAccount.address, 0, 72).accounts({user: admin, xnShard}).rpc();
+    expect((await program.account.userEthXnRecord.fetch(xnByEth)).version).to.equal(2);
+
+    const unitsOf = async (method) => {
+      const tx = await method.accounts({user: admin, xnShard}).transaction();
+      tx.feePayer = admin;
+      tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+      const {value} = await provider.connection.simulateTransaction(tx);
+      expect(value.err).to.equal(null);
+      return value.unitsConsumed;
+    };
+    const v1 = await unitsOf(program.methods.mineHashes(ethAccount.address, 0, 1, ethAccount.addressStr));
+    const v2 = await unitsOf(program.methods.mineHashesV2(ethAccount.address, 0, 1));
+    console.log('mine_hashes / mine_hashes_v2 compute units:', v1, v2);
+    expect(v2).to.be.lessThan(v1);
+  });
+
+  it("Mining takes 1 to max_hashes iterations", async () => {
+    const ethAccount = {address: Array(20).fill(5), addressStr: '0x' + '05'.repeat(20)};
+    await program.methods.mineHashes(ethAccount.address, 0, 1, ethAccount.addressStr).accounts({user: admin, xnShard}).rpc();
+    for (const iterations of [0, 73]) {
+      try {
+        await program.methods.mineHashes(ethAccount.address, 0, iterations, ethAccount.addressStr).accounts({user: admin, xnShard}).rpc();
+        expect.fail('should have failed');
+      } catch (e) {
+        expect(e.error.errorCode.code).to.equal('InvalidIterations');
+      }
+    }
+  });
+
+  it("Mining mixes in the slot hash when enabled", async () => {
+    const ethAccount = {address: Array(20).fill(3), addressStr: '0x' + '03'.repeat(20)};
+    await program.methods.updateParams(0, {...minerParams, mixSlotHash: true}).accounts({admin}).rpc();
+    const [params] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("xn-miner-params"), Buffer.from([0])],
+        program.programId
+    );
+    expect((await program.account.minerParams.fetch(params)).mixSlotHash).to.equal(true);
+
+    await program.methods.mineHashes(ethAccount.address, 0, 72, ethAccount.addressStr).accounts({user: admin, xnShard}).rpc();
+    try {
+      await program.methods.mineHashes(ethAccount.address, 0, 72, ethAccount.addressStr)
+          .accountsPartial({user: admin, xnShard, slotHashes: web3.SYSVAR_CLOCK_PUBKEY})
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('ConstraintAddress');
+    }
+    await program.methods.updateParams(0, minerParams).accounts({admin}).rpc();
+  });
+
+  it("Mining goes to the user's shard until totals are cranked", async () => {
+    const ethAccount = {address: Array(20).fill(4), addressStr: '0x' + '04'.repeat(20)};
+    const [global] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("xn-miner-global"), Buffer.from([0])],
+        program.programId
+    );
+    await program.methods.crankTotals(0).remainingAccounts([{pubkey: xnShard, isWritable: true, isSigner: false}]).rpc();
+    const before = await program.account.globalXnRecord.fetch(global);
+
+    await program.methods.mineHashes(ethAccount.address, 0, 72, ethAccount.addressStr).accounts({user: admin, xnShard}).rpc();
+    const shard = await program.account.globalXnShard.fetch(xnShard);
+    expect(shard.shard).to.equal(admin.toBytes()[0] % 16);
+    expect((await program.account.globalXnRecord.fetch(global)).hashes.toString()).to.equal(before.hashes.toString());
+
+    try {
+      await program.methods.mineHashes(ethAccount.address, 0, 72, ethAccount.addressStr)
+          .accounts({user: admin, xnShard: global})
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('ConstraintSeeds');
+    }
+
+    // the shard passed twice is only counted once
+    const shardMeta = {pubkey: xnShard, isWritable: true, isSigner: false};
+    await program.methods.crankTotals(0).remainingAccounts([shardMeta, shardMeta]).rpc();
+    const after = await program.account.globalXnRecord.fetch(global);
+    expect(after.hashes.sub(before.hashes).toString()).to.equal(shard.hashes.toString());
+    expect(after.points.sub(before.points).toString()).to.equal(shard.points.toString());
+    expect((await program.account.globalXnShard.fetch(xnShard)).hashes.toNumber()).to.equal(0);
+  });
+
+  it("Mining is rejected once end slot is reached", async () => {
+    const ethAccount = {address: Array(20).fill(1), addressStr: '0x' + '01'.repeat(20)};
+    await program.methods.updateParams(0, {...minerParams, endSlot: new BN(1)}).accounts({admin}).rpc();
+
+    const status = await program.methods.getMiningStatus(0).view();
+    expect(status.isOver).to.equal(true);
+    expect(status.remainingCycles.toNumber()).to.equal(0);
+
+    try {
+      await program.methods.mineHashes(ethAccount.address, 0, 72, ethAccount.addressStr).accounts({user: admin, xnShard}).rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('MiningIsOver');
+    }
+    await program.methods.updateParams(0, minerParams).accounts({admin}).rpc();
+  });
+
+  it("User record can not be closed while mining is open", async () => {
+    const ethAccount = {address: Array(20).fill(1), addressStr: '0x' + '01'.repeat(20)};
+    await program.methods.mineHashes(ethAccount.address, 0, 72, ethAccount.addressStr).accounts({user: admin, xnShard}).rpc();
+    try {
+      await program.methods.closeUserRecord(0).accounts({user: admin}).rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('MiningIsNotOver');
+    }
+  });
+
+  it("Old admin can not init miner after the transfer", async () => {
+    try {
+      await program.methods.initMiner(1, minerParams)
+          .accounts({admin: stranger.publicKey})
+          .signers([stranger])
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotAdmin');
+    }
+  });
+});
@@ -0,0 +1,207 @@
+import * as anchor from "@coral-xyz/anchor";
+import {Program, web3} from "@coral-xyz/anchor";
+import {SolXenMinter} from "../target/types/sol_xen_minter";
+import {expect} from "chai";
+import NodeWallet from "@coral-xyz/anchor/dist/cjs/nodewallet";
+
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+const TOKEN_METADATA_PROGRAM_ID = new web3.PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+const tokenParams = {
+  name: "solXEN",
+  symbol: "solXEN",
+  uri: 'https://solxen.io/metadata.json',
+  decimals: 6,
+};
+
+describe("sol-xen-minter admin", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.SolXenMinter as Program<SolXenMinter>;
+  const provider = anchor.getProvider();
+  const admin = NodeWallet.local().publicKey;
+  const stranger = web3.Keypair.generate();
+  const newMiner = web3.Keypair.generate().publicKey;
+
+  const [programData] = web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+  );
+  const [config] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("xn-minter-config")],
+      program.programId
+  );
+  const [mint] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("mint")],
+      program.programId
+  );
+
+  const isRegistered = async (kind: number, programId: web3.PublicKey) => {
+    const {miners} = await program.account.minterConfig.fetch(config);
+    return miners.some(entry => entry.kind === kind && entry.programId.equals(programId));
+  };
+
+  before(async () => {
+    const sig = await provider.connection.requestAirdrop(stranger.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig);
+  });
+
+  it("Config can not be initialized by a non upgrade authority", async () => {
+    try {
+      await program.methods.initConfig()
+          .accounts({admin: stranger.publicKey, programData})
+          .signers([stranger])
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotAdmin');
+    }
+  });
+
+  it("Config can be initialized by the upgrade authority", async () => {
+    await program.methods.initConfig().accounts({admin, programData}).rpc();
+    const configAccount = await program.account.minterConfig.fetch(config);
+    expect(configAccount.admin.toBase58()).to.equal(admin.toBase58());
+    // one entry per kind for the miner, legacy programs aren't registered
+    expect(configAccount.miners.length).to.equal(4);
+  });
+
+  it("Legacy miners can not be registered", async () => {
+    const legacyMiner = new web3.PublicKey("2Ewuie2KnTvMLwGqKWvEM1S2gUStHzDUfrANdJfu45QJ");
+    try {
+      await program.methods.addMiner(1, legacyMiner).accounts({admin}).rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('LegacyMiner');
+    }
+  });
+
+  it("Miners can not be registered by a non-admin", async () => {
+    try {
+      await program.methods.addMiner(0, newMiner)
+          .accounts({admin: stranger.publicKey})
+          .signers([stranger])
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotAdmin');
+    }
+  });
+
+  it("Miners can be registered and removed by the admin", async () => {
+    await program.methods.addMiner(1, newMiner).accounts({admin}).rpc();
+    expect(await isRegistered(1, newMiner)).to.be.true;
+    expect(await isRegistered(2, newMiner)).to.be.false;
+
+    try {
+      await program.methods.addMiner(1, newMiner).accounts({admin}).rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('MinerAlreadyRegistered');
+    }
+
+    await program.methods.removeMiner(1, newMiner).accounts({admin}).rpc();
+    expect(await isRegistered(1, newMiner)).to.be.false;
+  });
+
+  it("Frozen miners can not be removed", async () => {
+    await program.methods.addMiner(3, newMiner).accounts({admin}).rpc();
+    await program.methods.freezeMiner(3, newMiner).accounts({admin}).rpc();
+    try {
+      await program.methods.removeMiner(3, newMiner).accounts({admin}).rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('MinerIsFrozen');
+    }
+    expect(await isRegistered(3, newMiner)).to.be.true;
+  });
+
+  it("Mint can not be created by a non-admin", async () => {
+    try {
+      await program.methods.createMint(tokenParams)
+          .accounts({admin: stranger.publicKey})
+          .signers([stranger])
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotAdmin');
+    }
+  });
+
+  it("Token-2022 mint can not be created by a non-admin", async () => {
+    try {
+      await program.methods.createMint2022(tokenParams)
+          .accounts({admin: stranger.publicKey})
+          .signers([stranger])
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotAdmin');
+    }
+  });
+
+  it("Mint can not be created with a spoofed metadata account", async () => {
+    // metadata PDA of another mint
+    const [spoofed] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), web3.Keypair.generate().publicKey.toBuffer()],
+        TOKEN_METADATA_PROGRAM_ID
+    );
+    try {
+      await program.methods.createMint(tokenParams)
+          .accountsPartial({admin, metadata: spoofed})
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('ConstraintSeeds');
+    }
+  });
+
+  it("Supply can not be tracked by a non-admin", async () => {
+    try {
+      await program.methods.initSupply(new anchor.BN(0))
+          .accounts({admin: stranger.publicKey})
+          .signers([stranger])
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotAdmin');
+    }
+  });
+
+  it("Payout can only be set once", async () => {
+    const [payout] = web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("xn-payout"), stranger.publicKey.toBuffer()],
+        program.programId
+    );
+    const coldWallet = web3.Keypair.generate().publicKey;
+    await program.methods.setPayout(coldWallet)
+        .accounts({user: stranger.publicKey})
+        .signers([stranger])
+        .rpc();
+    expect((await program.account.payoutRecord.fetch(payout)).recipient.toBase58()).to.equal(coldWallet.toBase58());
+
+    let failed = false;
+    try {
+      await program.methods.setPayout(stranger.publicKey)
+          .accounts({user: stranger.publicKey})
+          .signers([stranger])
+          .rpc();
+    } catch (e) {
+      failed = true;
+    }
+    expect(failed).to.be.true;
+    expect((await program.account.payoutRecord.fetch(payout)).recipient.toBase58()).to.equal(coldWallet.toBase58());
+  });
+
+  it("Mint authority can not be revoked by a non-admin", async () => {
+    try {
+      await program.methods.revokeMintAuthority()
+          .accountsPartial({admin: stranger.publicKey, mintAccount: mint})
+          .signers([stranger])
+          .rpc();
+      expect.fail('should have failed');
+    } catch (e) {
+      expect(e.error.errorCode.code).to.equal('NotAdmin');
+    }
+  });
+});